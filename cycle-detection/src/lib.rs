@@ -0,0 +1,179 @@
+use std::{collections::HashMap, hash::Hash};
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Cycle {
+    pub mu: usize,
+    pub lambda: usize,
+}
+
+#[derive(Debug)]
+pub struct History<T> {
+    pub cycle: Cycle,
+    states: Vec<T>,
+}
+
+impl Cycle {
+    pub fn reduce(&self, step: usize) -> usize {
+        if step < self.mu {
+            return step;
+        }
+
+        self.mu + (step - self.mu) % self.lambda
+    }
+
+    pub fn jump<I: Iterator>(&self, mut states: I, step: usize) -> Option<I::Item> {
+        states.nth(self.reduce(step))
+    }
+}
+
+impl From<Cycle> for (usize, usize) {
+    fn from(value: Cycle) -> Self {
+        (value.mu, value.lambda)
+    }
+}
+
+impl<T> History<T> {
+    pub fn state_at(&self, step: usize) -> &T {
+        &self.states[self.cycle.reduce(step)]
+    }
+
+    pub fn states(&self) -> &[T] {
+        &self.states
+    }
+}
+
+pub fn hashed<I>(states: I) -> Option<History<I::Item>>
+where
+    I: IntoIterator,
+    I::Item: Hash + Eq + Clone,
+{
+    let mut seen = HashMap::new();
+    let mut history = vec![];
+
+    for state in states {
+        if let Some(&mu) = seen.get(&state) {
+            return Some(History {
+                cycle: Cycle {
+                    mu,
+                    lambda: history.len() - mu,
+                },
+                states: history,
+            });
+        }
+
+        seen.insert(state.clone(), history.len());
+        history.push(state);
+    }
+
+    None
+}
+
+pub fn floyd<I>(states: I) -> Option<Cycle>
+where
+    I: Iterator + Clone,
+    I::Item: Eq,
+{
+    let mut tortoise = states.clone();
+    let mut hare = states.clone();
+
+    loop {
+        let t = tortoise.next()?;
+        hare.next()?;
+        let h = hare.next()?;
+
+        if t == h {
+            break;
+        }
+    }
+
+    let mut from_start = states.clone();
+    let mut mu = 0;
+    while from_start.next()? != tortoise.next()? {
+        mu += 1;
+    }
+
+    let mut lambda = 1;
+    let mut hare = states.skip(mu);
+    let first = hare.next()?;
+    while hare.next()? != first {
+        lambda += 1;
+    }
+
+    Some(Cycle { mu, lambda })
+}
+
+pub fn brent<I>(states: I) -> Option<Cycle>
+where
+    I: Iterator + Clone,
+    I::Item: Eq,
+{
+    let mut hare = states.clone();
+    let mut tortoise = hare.next()?;
+    let mut power = 1;
+    let mut lambda = 1;
+
+    loop {
+        let h = hare.next()?;
+
+        if h == tortoise {
+            break;
+        }
+
+        if power == lambda {
+            tortoise = h;
+            power *= 2;
+            lambda = 0;
+        }
+
+        lambda += 1;
+    }
+
+    let mut tortoise = states.clone();
+    let mut hare = states.skip(lambda);
+    let mut mu = 0;
+    while tortoise.next()? != hare.next()? {
+        mu += 1;
+    }
+
+    Some(Cycle { mu, lambda })
+}
+
+#[cfg(test)]
+fn sequence(start: u64) -> impl Iterator<Item = u64> + Clone {
+    std::iter::successors(Some(start), |x| Some((x * x + 1) % 255))
+}
+
+#[test]
+fn should_detect_cycle_by_hashing() {
+    let history = hashed(sequence(3)).unwrap();
+
+    assert_eq!(history.states()[..5], [3, 10, 101, 2, 5]);
+    assert_eq!(history.cycle, Cycle { mu: 2, lambda: 6 });
+}
+
+#[test]
+fn should_agree_on_cycle_with_every_strategy() {
+    for start in 0..255 {
+        let expected = hashed(sequence(start)).unwrap().cycle;
+
+        assert_eq!(floyd(sequence(start)), Some(expected));
+        assert_eq!(brent(sequence(start)), Some(expected));
+    }
+}
+
+#[test]
+fn should_jump_to_step_n() {
+    let history = hashed(sequence(3)).unwrap();
+
+    let expected = sequence(3).nth(1_000).unwrap();
+
+    assert_eq!(*history.state_at(1_000), expected);
+    assert_eq!(history.cycle.jump(sequence(3), 1_000), Some(expected));
+}
+
+#[test]
+fn should_not_detect_cycle_in_finite_sequence() {
+    assert!(hashed(0..10).is_none());
+    assert_eq!(floyd(0..10), None);
+    assert_eq!(brent(0..10), None);
+}
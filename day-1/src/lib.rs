@@ -57,6 +57,42 @@ const LETTERS_TO_DIGITS: [(&str, i8); 9] = [
     ("nine", 9),
 ];
 
+const GERMAN: [(&str, u8); 9] = [
+    ("eins", 1),
+    ("zwei", 2),
+    ("drei", 3),
+    ("vier", 4),
+    ("fünf", 5),
+    ("sechs", 6),
+    ("sieben", 7),
+    ("acht", 8),
+    ("neun", 9),
+];
+
+const FRENCH: [(&str, u8); 9] = [
+    ("un", 1),
+    ("deux", 2),
+    ("trois", 3),
+    ("quatre", 4),
+    ("cinq", 5),
+    ("six", 6),
+    ("sept", 7),
+    ("huit", 8),
+    ("neuf", 9),
+];
+
+const SPANISH: [(&str, u8); 9] = [
+    ("uno", 1),
+    ("dos", 2),
+    ("tres", 3),
+    ("cuatro", 4),
+    ("cinco", 5),
+    ("seis", 6),
+    ("siete", 7),
+    ("ocho", 8),
+    ("nueve", 9),
+];
+
 fn replace_letter_with_digit(input: &str) -> String {
     if input.is_empty() {
         String::new()
@@ -89,6 +125,299 @@ fn parse_i8(str: &str) -> Result<i8, String> {
         .map_err(|_| String::from("Invalid string provided"))
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CalibrationError {
+    NoDigit { line: usize },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum WordListError {
+    Io(std::io::ErrorKind),
+    InvalidLine { line: usize, content: String },
+    Conflict { word: String, values: (u8, u8) },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Language {
+    English,
+    German,
+    French,
+    Spanish,
+}
+
+#[derive(Debug, Clone)]
+pub struct DigitMatcher {
+    forward: Automaton,
+    backward: Automaton,
+}
+
+#[derive(Debug, Clone)]
+struct Automaton {
+    transitions: Vec<[usize; 256]>,
+    outputs: Vec<Option<(usize, u8)>>,
+    max_len: usize,
+}
+
+/// Sums the calibration values of all lines, failing on the first line that
+/// contains neither a digit nor a word known to the matcher.
+///
+/// # Errors
+///
+/// Returns [`CalibrationError::NoDigit`] with the zero-based line index.
+pub fn try_calibration_value(
+    input: &[&str],
+    matcher: &DigitMatcher,
+) -> Result<u32, CalibrationError> {
+    input
+        .iter()
+        .enumerate()
+        .map(|(line, x)| {
+            matcher
+                .calibration(x)
+                .ok_or(CalibrationError::NoDigit { line })
+        })
+        .sum()
+}
+
+impl DigitMatcher {
+    #[must_use]
+    pub fn new(words: &[(&str, u8)]) -> Self {
+        let digits = ["0", "1", "2", "3", "4", "5", "6", "7", "8", "9"];
+        let patterns = digits
+            .iter()
+            .zip(0..)
+            .map(|(digit, value)| (digit.as_bytes().to_vec(), value))
+            .chain(
+                words
+                    .iter()
+                    .map(|(word, value)| (word.as_bytes().to_vec(), *value)),
+            )
+            .collect::<Vec<_>>();
+
+        let reversed = patterns
+            .iter()
+            .map(|(word, value)| (word.iter().rev().copied().collect(), *value))
+            .collect::<Vec<_>>();
+
+        Self {
+            forward: Automaton::new(&patterns),
+            backward: Automaton::new(&reversed),
+        }
+    }
+
+    #[must_use]
+    pub fn digits_only() -> Self {
+        Self::new(&[])
+    }
+
+    #[must_use]
+    pub fn english() -> Self {
+        Self::new(&Language::English.words())
+    }
+
+    /// Combines the dictionaries of several languages. A word shared by two
+    /// languages is fine as long as it stands for the same digit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WordListError::Conflict`] if two languages map the same word
+    /// to different digits.
+    pub fn for_languages(languages: &[Language]) -> Result<Self, WordListError> {
+        let words = languages
+            .iter()
+            .flat_map(|language| language.words())
+            .collect::<Vec<_>>();
+
+        check_conflicts(&words)?;
+
+        Ok(Self::new(&words))
+    }
+
+    /// Builds a matcher from a word list file, see [`parse_words`] for the format.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`WordListError::Io`] if the file cannot be read,
+    /// [`WordListError::InvalidLine`] for malformed entries and
+    /// [`WordListError::Conflict`] if a word is listed with two digits.
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, WordListError> {
+        let content = std::fs::read_to_string(path).map_err(|e| WordListError::Io(e.kind()))?;
+        let words = parse_words(&content)?;
+        let words = words
+            .iter()
+            .map(|(word, value)| (word.as_str(), *value))
+            .collect::<Vec<_>>();
+
+        check_conflicts(&words)?;
+
+        Ok(Self::new(&words))
+    }
+
+    #[must_use]
+    pub fn first_and_last(&self, line: &str) -> Option<(u8, u8)> {
+        let first = self.forward.leftmost(line.bytes())?;
+        let last = self.backward.leftmost(line.bytes().rev())?;
+
+        Some((first, last))
+    }
+
+    #[must_use]
+    pub fn calibration(&self, line: &str) -> Option<u32> {
+        self.first_and_last(line)
+            .map(|(first, last)| u32::from(first) * 10 + u32::from(last))
+    }
+}
+
+impl Language {
+    #[must_use]
+    pub fn words(self) -> Vec<(&'static str, u8)> {
+        match self {
+            Language::English => LETTERS_TO_DIGITS
+                .map(|(word, value)| (word, value.unsigned_abs()))
+                .to_vec(),
+            Language::German => GERMAN.to_vec(),
+            Language::French => FRENCH.to_vec(),
+            Language::Spanish => SPANISH.to_vec(),
+        }
+    }
+}
+
+fn check_conflicts(words: &[(&str, u8)]) -> Result<(), WordListError> {
+    let mut seen = std::collections::HashMap::new();
+
+    for (word, value) in words {
+        match seen.insert(*word, *value) {
+            Some(first) if first != *value => {
+                return Err(WordListError::Conflict {
+                    word: (*word).to_string(),
+                    values: (first, *value),
+                })
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Parses a word list with one `word digit` pair per line. Blank lines and
+/// lines starting with `#` are skipped.
+///
+/// # Errors
+///
+/// Returns [`WordListError::InvalidLine`] with the one-based line number when
+/// a line does not consist of a word followed by a single digit.
+pub fn parse_words(content: &str) -> Result<Vec<(String, u8)>, WordListError> {
+    content
+        .lines()
+        .enumerate()
+        .map(|(i, x)| (i + 1, x.trim()))
+        .filter(|(_, x)| !x.is_empty() && !x.starts_with('#'))
+        .map(|(line, x)| {
+            let invalid = || WordListError::InvalidLine {
+                line,
+                content: x.to_string(),
+            };
+
+            match x.split_whitespace().collect::<Vec<_>>().as_slice() {
+                [word, digit] => digit
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|digit| *digit <= 9)
+                    .map(|digit| ((*word).to_string(), digit))
+                    .ok_or_else(invalid),
+                _ => Err(invalid()),
+            }
+        })
+        .collect()
+}
+
+impl Automaton {
+    fn new(patterns: &[(Vec<u8>, u8)]) -> Self {
+        let mut transitions = vec![[0; 256]];
+        let mut outputs = vec![None];
+        let mut children = vec![[false; 256]];
+
+        for (word, value) in patterns {
+            let mut state = 0;
+
+            for byte in word {
+                let byte = usize::from(*byte);
+
+                if !children[state][byte] {
+                    children[state][byte] = true;
+                    transitions[state][byte] = transitions.len();
+                    transitions.push([0; 256]);
+                    outputs.push(None);
+                    children.push([false; 256]);
+                }
+
+                state = transitions[state][byte];
+            }
+
+            outputs[state] = Some((word.len(), *value));
+        }
+
+        // Breadth-first over the trie, turning it into a DFA: missing edges
+        // follow the failure link, and every state inherits the longest match
+        // of its failure state when it has none of its own.
+        let mut fail = vec![0; transitions.len()];
+        let mut queue = std::collections::VecDeque::from([0]);
+
+        while let Some(state) = queue.pop_front() {
+            for byte in 0..256 {
+                if children[state][byte] {
+                    let child = transitions[state][byte];
+
+                    if state != 0 {
+                        fail[child] = transitions[fail[state]][byte];
+                        outputs[child] = outputs[child].or(outputs[fail[child]]);
+                    }
+
+                    queue.push_back(child);
+                } else if state != 0 {
+                    transitions[state][byte] = transitions[fail[state]][byte];
+                }
+            }
+        }
+
+        Self {
+            transitions,
+            outputs,
+            max_len: patterns
+                .iter()
+                .map(|(word, _)| word.len())
+                .max()
+                .unwrap_or(0),
+        }
+    }
+
+    // Returns the value of the match that starts first. Once a match is found
+    // we keep scanning only while a longer pattern could still start earlier.
+    fn leftmost(&self, bytes: impl Iterator<Item = u8>) -> Option<u8> {
+        let mut state = 0;
+        let mut best: Option<(usize, u8)> = None;
+
+        for (end, byte) in bytes.enumerate() {
+            if best.is_some_and(|(start, _)| end >= start + self.max_len) {
+                break;
+            }
+
+            state = self.transitions[state][usize::from(byte)];
+
+            if let Some((len, value)) = self.outputs[state] {
+                let start = end + 1 - len;
+
+                if best.is_none_or(|(best_start, _)| start < best_start) {
+                    best = Some((start, value));
+                }
+            }
+        }
+
+        best.map(|(_, value)| value)
+    }
+}
+
 #[test]
 fn should_find_digits_in_string() {
     let input = "pqr3stu8vwx";
@@ -214,3 +543,105 @@ fn should_replace_letter_with_digit() {
 
     assert_eq!(digit, "1ne");
 }
+
+#[test]
+fn should_match_first_and_last_in_one_pass() {
+    let matcher = DigitMatcher::english();
+
+    assert_eq!(matcher.first_and_last("eightwothree"), Some((8, 3)));
+    assert_eq!(matcher.first_and_last("zoneight234"), Some((1, 4)));
+    assert_eq!(matcher.first_and_last("xtwone3four"), Some((2, 4)));
+    assert_eq!(matcher.first_and_last("7pqrstsixteen"), Some((7, 6)));
+    assert_eq!(matcher.first_and_last("oneight"), Some((1, 8)));
+}
+
+#[test]
+fn should_support_custom_word_tables() {
+    let matcher = DigitMatcher::new(&[("zero", 0), ("one", 1), ("nineteen", 9)]);
+
+    assert_eq!(matcher.calibration("zeroabcone"), Some(1));
+    assert_eq!(matcher.calibration("nineteen"), Some(99));
+    assert_eq!(
+        DigitMatcher::digits_only().calibration("two1nine"),
+        Some(11)
+    );
+}
+
+#[test]
+fn should_report_lines_without_digit() {
+    let input = ["two1nine", "abc", "treb7uchet"];
+
+    let result = try_calibration_value(&input, &DigitMatcher::english());
+
+    assert_eq!(result, Err(CalibrationError::NoDigit { line: 1 }));
+}
+
+#[test]
+fn should_extract_number_from_eightwothree_in_every_language() {
+    let cases = [
+        (Language::English, "eightwothree", (8, 3)),
+        (Language::English, "oneight", (1, 8)),
+        (Language::German, "achtzweidrei", (8, 3)),
+        (Language::German, "sechsieben", (6, 7)),
+        (Language::French, "huitdeuxtrois", (8, 3)),
+        (Language::French, "cinquatre", (5, 4)),
+        (Language::Spanish, "ochodostres", (8, 3)),
+        (Language::Spanish, "unocho", (1, 8)),
+    ];
+
+    for (language, input, expected) in cases {
+        let matcher = DigitMatcher::for_languages(&[language]).unwrap();
+
+        assert_eq!(matcher.first_and_last(input), Some(expected), "{input}");
+    }
+}
+
+#[test]
+fn should_extract_number_across_combined_dictionaries() {
+    let matcher = DigitMatcher::for_languages(&[Language::German, Language::Spanish]).unwrap();
+
+    assert_eq!(matcher.first_and_last("xfünfunocho"), Some((5, 8)));
+    assert_eq!(matcher.first_and_last("einsdos"), Some((1, 2)));
+    assert!(DigitMatcher::for_languages(&[Language::English, Language::French]).is_ok());
+}
+
+#[test]
+fn should_parse_word_list() {
+    let content = "# Dutch\neen 1\n\ntwee 2\n";
+
+    assert_eq!(
+        parse_words(content),
+        Ok(vec![("een".to_string(), 1), ("twee".to_string(), 2)])
+    );
+    assert_eq!(
+        parse_words("een 1\ndrie 33"),
+        Err(WordListError::InvalidLine {
+            line: 2,
+            content: "drie 33".to_string()
+        })
+    );
+}
+
+#[test]
+fn should_load_word_list_from_file() {
+    let path = std::env::temp_dir().join(format!("day-1-word-list-{}.txt", std::process::id()));
+    std::fs::write(&path, "een 1\ntwee 2\nacht 8\n").unwrap();
+    let matcher = DigitMatcher::from_file(&path);
+
+    std::fs::write(&path, "een 1\neen 2\n").unwrap();
+    let conflict = DigitMatcher::from_file(&path).err();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(matcher.unwrap().first_and_last("eentweeacht"), Some((1, 8)));
+    assert_eq!(
+        conflict,
+        Some(WordListError::Conflict {
+            word: "een".to_string(),
+            values: (1, 2)
+        })
+    );
+    assert_eq!(
+        DigitMatcher::from_file(path.with_extension("missing")).err(),
+        Some(WordListError::Io(std::io::ErrorKind::NotFound))
+    );
+}
@@ -10,8 +10,10 @@ fn main() {
 
     println!("Trying to solve part 2...");
     let before = std::time::Instant::now();
+    let lines = input.lines().collect::<Vec<_>>();
     let calibration_value_two =
-        day_1::calculate_calibration_value_with_letters(input.lines().collect());
+        day_1::try_calibration_value(&lines, &day_1::DigitMatcher::english())
+            .expect("Every line should contain a digit");
     let after = std::time::Instant::now();
     println!("Time taken: {:?}", after.duration_since(before));
     println!("Solution is: {}", calibration_value_two);
@@ -23,3 +23,21 @@ fn should_sum_up_calibration_numbers_with_letters() {
 
     assert_eq!(sum_of_calibration_values, 281);
 }
+
+#[test]
+fn should_sum_up_calibration_numbers_with_matcher() {
+    let input = [
+        "two1nine",
+        "eightwothree",
+        "abcone2threexyz",
+        "xtwone3four",
+        "4nineeightseven2",
+        "zoneight234",
+        "7pqrstsixteen",
+    ];
+
+    let sum_of_calibration_values =
+        day_1::try_calibration_value(&input, &day_1::DigitMatcher::english());
+
+    assert_eq!(sum_of_calibration_values, Ok(281));
+}
@@ -1,46 +1,52 @@
-#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Hash)]
-enum Dir {
-    N,
-    S,
-    E,
-    W,
-}
+use grid::{
+    image::{Image, Rgb},
+    Dir, Grid, Pos,
+};
 
 pub fn calculate_steps(board: Vec<&str>) -> i32 {
-    let start = find_start_position(board.clone()).unwrap();
-
-    let longest_path = [
-        walk(board.clone(), (start, Dir::N), 0),
-        walk(board.clone(), (start, Dir::S), 0),
-        walk(board.clone(), (start, Dir::E), 0),
-        walk(board.clone(), (start, Dir::W), 0),
-    ];
+    let board = Grid::parse(&board);
+    let start = find_start_position(&board).unwrap();
 
-    *longest_path.iter().max().unwrap()
+    Dir::ALL
+        .into_iter()
+        .map(|dir| walk(&board, (start, dir), 0))
+        .max()
+        .unwrap()
 }
 
 pub fn calculate_number_of_enclosing_points(board: Vec<&str>) -> i32 {
-    let path = find_longest_path(board.clone());
+    let path = find_longest_path(board)
+        .into_iter()
+        .map(|(row, col)| (row as i32, col as i32))
+        .collect::<Vec<(i32, i32)>>();
     let area = shoelace_formula(path.clone());
 
     area + 1 - (path.len() / 2) as i32
 }
 
-fn find_longest_path(board: Vec<&str>) -> Vec<(i32, i32)> {
-    let start = find_start_position(board.clone()).unwrap();
+pub fn render_loop(board: Vec<&str>) -> Image {
+    let path = find_longest_path(board.clone());
 
-    let longest_path = [
-        find_path(board.clone(), (start, Dir::N)),
-        find_path(board.clone(), (start, Dir::S)),
-        find_path(board.clone(), (start, Dir::E)),
-        find_path(board.clone(), (start, Dir::W)),
-    ];
+    Image::render(&Grid::parse(&board), palette).overlay(path, [255, 64, 64])
+}
 
-    longest_path
-        .iter()
-        .max_by_key(|path| path.clone().len())
+fn palette(tile: &u8) -> Rgb {
+    match tile {
+        b'S' => [64, 200, 64],
+        b'.' => [24, 24, 24],
+        _ => [128, 128, 128],
+    }
+}
+
+fn find_longest_path(board: Vec<&str>) -> Vec<Pos> {
+    let board = Grid::parse(&board);
+    let start = find_start_position(&board).unwrap();
+
+    Dir::ALL
+        .into_iter()
+        .map(|dir| find_path(&board, (start, dir)))
+        .max_by_key(|path| path.len())
         .unwrap()
-        .clone()
 }
 
 fn shoelace_formula(points: Vec<(i32, i32)>) -> i32 {
@@ -54,90 +60,64 @@ fn shoelace_formula(points: Vec<(i32, i32)>) -> i32 {
     sum.abs() / 2
 }
 
-fn find_start_position(board: Vec<&str>) -> Option<(i32, i32)> {
-    board
-        .into_iter()
-        .enumerate()
-        .flat_map(|(row, line)| {
-            line.chars()
-                .enumerate()
-                .filter(|(_, tile)| *tile == 'S')
-                .map(move |(col, _)| (row as i32, col as i32))
-        })
-        .next()
-}
-
-fn next_position((row, col): (i32, i32), tile: char, dir: Dir) -> Option<((i32, i32), Dir)> {
-    match (tile, dir) {
-        ('|', Dir::S) => Some(((row + 1, col), Dir::S)),
-        ('|', Dir::N) => Some(((row - 1, col), Dir::N)),
-        ('-', Dir::E) => Some(((row, col + 1), Dir::E)),
-        ('-', Dir::W) => Some(((row, col - 1), Dir::W)),
-        ('L', Dir::S) => Some(((row, col + 1), Dir::E)),
-        ('L', Dir::W) => Some(((row - 1, col), Dir::N)),
-        ('J', Dir::S) => Some(((row, col - 1), Dir::W)),
-        ('J', Dir::E) => Some(((row - 1, col), Dir::N)),
-        ('7', Dir::N) => Some(((row, col - 1), Dir::W)),
-        ('7', Dir::E) => Some(((row + 1, col), Dir::S)),
-        ('F', Dir::N) => Some(((row, col + 1), Dir::E)),
-        ('F', Dir::W) => Some(((row + 1, col), Dir::S)),
-        ('S', Dir::W) => Some(((row, col + 1), Dir::W)),
-        ('S', Dir::E) => Some(((row, col - 1), Dir::E)),
-        ('S', Dir::N) => Some(((row - 1, col), Dir::N)),
-        ('S', Dir::S) => Some(((row + 1, col), Dir::S)),
-        _ => None,
-    }
+fn find_start_position(board: &Grid<u8>) -> Option<Pos> {
+    board.position(|tile| *tile == b'S')
 }
 
-fn walk(board: Vec<&str>, pos: ((i32, i32), Dir), steps: i32) -> i32 {
-    let (row, col) = pos.0;
+fn next_position(pos: Pos, tile: u8, dir: Dir) -> Option<(Pos, Dir)> {
+    let next_dir = match (tile, dir) {
+        (b'|', Dir::South | Dir::North) => dir,
+        (b'-', Dir::East | Dir::West) => dir,
+        (b'L', Dir::South) => Dir::East,
+        (b'L', Dir::West) => Dir::North,
+        (b'J', Dir::South) => Dir::West,
+        (b'J', Dir::East) => Dir::North,
+        (b'7', Dir::North) => Dir::West,
+        (b'7', Dir::East) => Dir::South,
+        (b'F', Dir::North) => Dir::East,
+        (b'F', Dir::West) => Dir::South,
+        (b'S', _) => dir,
+        _ => return None,
+    };
 
-    if row < 0 || col < 0 {
-        return steps;
-    }
+    let (dr, dc) = next_dir.delta();
 
-    let tile = get_tile(board.clone(), pos.0);
-    let next_pos = tile.and_then(|tile| next_position(pos.0, tile, pos.1));
+    Some((
+        (pos.0.checked_add_signed(dr)?, pos.1.checked_add_signed(dc)?),
+        next_dir,
+    ))
+}
 
-    next_pos
-        .map(|next_pos| {
-            let next_tile = get_tile(board.clone(), next_pos.0);
+fn walk(board: &Grid<u8>, pos: (Pos, Dir), steps: i32) -> i32 {
+    let tile = board.get(pos.0);
+    let next_pos = tile.and_then(|tile| next_position(pos.0, *tile, pos.1));
 
-            match next_tile {
-                Some('S') => (steps + 1) / 2,
-                Some(_) => walk(board.clone(), next_pos, steps + 1),
-                None => steps + 1,
-            }
+    next_pos
+        .map(|next_pos| match board.get(next_pos.0) {
+            Some(b'S') => (steps + 1) / 2,
+            Some(_) => walk(board, next_pos, steps + 1),
+            None => steps + 1,
         })
         .unwrap_or(steps)
 }
 
-fn find_path(board: Vec<&str>, start: ((i32, i32), Dir)) -> Vec<(i32, i32)> {
+fn find_path(board: &Grid<u8>, start: (Pos, Dir)) -> Vec<Pos> {
     let mut path = vec![];
     let mut pos = start;
 
     loop {
-        let (row, col) = pos.0;
+        let tile = board[pos.0];
 
-        if row < 0 || col < 0 {
-            break;
-        }
-
-        let tile = get_tile(board.clone(), pos.0);
-        let next_pos = tile.and_then(|tile| next_position(pos.0, tile, pos.1));
-
-        match next_pos {
-            Some(next_pos) => {
-                let next_tile = get_tile(board.clone(), next_pos.0);
-                if next_tile == Some('S') {
-                    path.push(next_pos.0);
-                    break;
-                } else {
-                    path.push(pos.0);
-                    pos = next_pos;
-                }
+        match next_position(pos.0, tile, pos.1) {
+            Some(next_pos) if board.get(next_pos.0) == Some(&b'S') => {
+                path.push(next_pos.0);
+                break;
+            }
+            Some(next_pos) if board.contains(next_pos.0) => {
+                path.push(pos.0);
+                pos = next_pos;
             }
-            None => {
+            _ => {
                 path.push(pos.0);
                 break;
             }
@@ -147,10 +127,6 @@ fn find_path(board: Vec<&str>, start: ((i32, i32), Dir)) -> Vec<(i32, i32)> {
     path
 }
 
-fn get_tile(board: Vec<&str>, (row, col): (i32, i32)) -> Option<char> {
-    board[row as usize].chars().nth(col as usize)
-}
-
 #[test]
 fn should_count_number_of_steps_from_start() {
     let board = vec!["..F7.", ".FJ|.", "SJ.L7", "|F--J", "LJ..."];
@@ -193,43 +169,43 @@ fn should_find_the_longest_path_from_start() {
 fn should_calculate_next_position_for_north_to_east() {
     let position = (1, 2);
 
-    let next_position = next_position(position, 'L', Dir::S);
+    let next_position = next_position(position, b'L', Dir::South);
 
-    assert_eq!(next_position, Some(((1, 3), Dir::E)));
+    assert_eq!(next_position, Some(((1, 3), Dir::East)));
 }
 
 #[test]
 fn should_calculate_next_position_for_north_to_south() {
     let position = (1, 2);
 
-    let next_position = next_position(position, '|', Dir::S);
+    let next_position = next_position(position, b'|', Dir::South);
 
-    assert_eq!(next_position, Some(((2, 2), Dir::S)));
+    assert_eq!(next_position, Some(((2, 2), Dir::South)));
 }
 
 #[test]
 fn should_calculate_next_position_for_south_to_north() {
     let position = (1, 2);
 
-    let next_position = next_position(position, '|', Dir::N);
+    let next_position = next_position(position, b'|', Dir::North);
 
-    assert_eq!(next_position, Some(((0, 2), Dir::N)));
+    assert_eq!(next_position, Some(((0, 2), Dir::North)));
 }
 
 #[test]
 fn should_calculate_next_position_for_east_to_west() {
     let position = (1, 2);
 
-    let next_position = next_position(position, '-', Dir::E);
+    let next_position = next_position(position, b'-', Dir::East);
 
-    assert_eq!(next_position, Some(((1, 3), Dir::E)));
+    assert_eq!(next_position, Some(((1, 3), Dir::East)));
 }
 
 #[test]
 fn should_find_start_position() {
     let board = vec![".....", ".S-7.", ".|.|.", ".L-J.", "....."];
 
-    let start_position = find_start_position(board);
+    let start_position = find_start_position(&Grid::parse(&board));
 
     assert_eq!(start_position, Some((1, 1)));
 }
@@ -238,7 +214,7 @@ fn should_find_start_position() {
 fn should_return_none_if_no_start_position_is_availabe() {
     let board = vec!["..F7.", ".FJ|.", "SJ.L7", "|F--J", "LJ..."];
 
-    let start_position = find_start_position(board);
+    let start_position = find_start_position(&Grid::parse(&board));
 
     assert_eq!(start_position, Some((2, 0)));
 }
@@ -251,3 +227,14 @@ fn should_calcualte_the_area_of_a_pentagon() {
 
     assert_eq!(area, 16);
 }
+
+#[test]
+fn should_render_loop_over_board() {
+    let board = vec!["..F7.", ".FJ|.", "SJ.L7", "|F--J", "LJ..."];
+
+    let image = render_loop(board);
+
+    assert_eq!(image.pixel((0, 0)), Some([24, 24, 24]));
+    assert_eq!(image.pixel((0, 2)), Some([191, 96, 96]));
+    assert_eq!(image.pixel((2, 0)), Some([159, 132, 64]));
+}
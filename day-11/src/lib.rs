@@ -1,137 +1,228 @@
-use std::cmp::{max, min};
+use grid::Grid;
+
+// Galaxies are numbered from 1 in reading order, like in the puzzle, so every
+// id taken or returned by `Universe` is 1-based.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Universe {
+    galaxies: Vec<(usize, usize)>,
+}
 
 pub fn sum_of_shortest_path_between_galaxies(input: Vec<&str>, factor: usize) -> usize {
-    let input = input.iter().map(|s| s.to_string()).collect::<Vec<String>>();
+    find_shortest_path_between_galaxies(&Grid::parse(&input), growth(factor))
+}
 
-    find_shortest_path_between_galaxies(input, factor - 1)
+pub fn expanded_galaxies(input: Vec<&str>, factor: usize) -> Vec<(usize, usize)> {
+    expand(&Grid::parse(&input), growth(factor))
 }
 
-fn find_shortest_path_between_galaxies(input: Vec<String>, factor: usize) -> usize {
-    let galaxies = find_galaxies(input.clone());
+// Every empty row or column is replaced by `factor` of them, so it grows by
+// `factor - 1`. A factor of 0 would remove space, which the puzzle never does.
+fn growth(factor: usize) -> usize {
+    assert!(
+        factor >= 1,
+        "expansion factor should be at least 1, got {}",
+        factor
+    );
 
-    let empty_rows = find_empty_rows(input.clone());
-    let empty_cols = find_empty_cols(input);
+    factor - 1
+}
 
-    galaxies
-        .iter()
-        .enumerate()
-        .flat_map(|(i, g)| {
-            galaxies.iter().skip(i + 1).map(|g2| {
-                let rows_between = empty_rows
-                    .iter()
-                    .filter(|r| **r > min(g.0, g2.0) && **r < max(g.0, g2.0))
-                    .count()
-                    * factor;
-
-                let cols_between = empty_cols
-                    .iter()
-                    .filter(|c| **c > min(g.1, g2.1) && **c < max(g.1, g2.1))
-                    .count()
-                    * factor;
-
-                manhatten_distance(*g, *g2) + rows_between + cols_between
-            })
-        })
-        .sum()
+impl Universe {
+    pub fn new(input: Vec<&str>, factor: usize) -> Self {
+        Self {
+            galaxies: expanded_galaxies(input, factor),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.galaxies.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.galaxies.is_empty()
+    }
+
+    pub fn galaxy(&self, id: usize) -> Option<(usize, usize)> {
+        self.galaxies.get(id.checked_sub(1)?).copied()
+    }
+
+    pub fn distance(&self, a: usize, b: usize) -> Option<usize> {
+        Some(manhatten_distance(self.galaxy(a)?, self.galaxy(b)?))
+    }
+
+    pub fn nearest(&self, id: usize, k: usize) -> Vec<(usize, usize)> {
+        let Some(galaxy) = self.galaxy(id) else {
+            return vec![];
+        };
+
+        let mut distances = self
+            .ids()
+            .zip(&self.galaxies)
+            .filter(|(other, _)| *other != id)
+            .map(|(other, g)| (other, manhatten_distance(galaxy, *g)))
+            .collect::<Vec<(usize, usize)>>();
+
+        distances.sort_by_key(|(other, distance)| (*distance, *other));
+        distances.truncate(k);
+        distances
+    }
+
+    // The header row and the first column hold the 1-based galaxy ids.
+    pub fn distance_matrix_csv(&self) -> String {
+        let header = self
+            .ids()
+            .map(|id| id.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+
+        let rows = self.ids().zip(&self.galaxies).map(|(id, a)| {
+            let distances = self
+                .galaxies
+                .iter()
+                .map(|b| manhatten_distance(*a, *b).to_string())
+                .collect::<Vec<String>>()
+                .join(",");
+
+            format!("{},{}", id, distances)
+        });
+
+        std::iter::once(format!("galaxy,{}", header))
+            .chain(rows)
+            .map(|line| line + "\n")
+            .collect()
+    }
+
+    fn ids(&self) -> impl Iterator<Item = usize> {
+        1..=self.galaxies.len()
+    }
 }
 
-fn manhatten_distance(a: (usize, usize), b: (usize, usize)) -> usize {
-    let rows = if a.0 > b.0 { a.0 - b.0 } else { b.0 - a.0 };
-    let cols = if a.1 > b.1 { a.1 - b.1 } else { b.1 - a.1 };
+fn find_shortest_path_between_galaxies(input: &Grid<u8>, factor: usize) -> usize {
+    let (rows, cols) = expand(input, factor).into_iter().unzip();
 
-    rows + cols
+    pairwise_distance_sum(rows) + pairwise_distance_sum(cols)
 }
 
-fn find_galaxies(input: Vec<String>) -> Vec<(usize, usize)> {
-    let mut galaxies = vec![];
+fn expand(input: &Grid<u8>, factor: usize) -> Vec<(usize, usize)> {
+    let row_offsets = offsets(find_empty_rows(input), input.rows(), factor);
+    let col_offsets = offsets(find_empty_cols(input), input.cols(), factor);
 
-    for (row_index, row) in input.iter().enumerate() {
-        for (col_index, col) in row.chars().enumerate() {
-            if col == '#' {
-                galaxies.push((row_index, col_index));
-            }
+    find_galaxies(input)
+        .iter()
+        .map(|(r, c)| (r + row_offsets[*r], c + col_offsets[*c]))
+        .collect()
+}
+
+fn offsets(empty: Vec<usize>, len: usize, factor: usize) -> Vec<usize> {
+    let mut offsets = vec![0; len];
+    let mut empty = empty.into_iter().peekable();
+    let mut count = 0;
+
+    for (i, offset) in offsets.iter_mut().enumerate() {
+        if empty.next_if_eq(&i).is_some() {
+            count += 1;
         }
+
+        *offset = count * factor;
     }
 
-    galaxies
+    offsets
 }
 
-fn find_empty_cols(input: Vec<String>) -> Vec<usize> {
-    input[0]
-        .chars()
+fn pairwise_distance_sum(mut values: Vec<usize>) -> usize {
+    values.sort_unstable();
+
+    values
+        .iter()
         .enumerate()
-        .filter(|(i, _)| input.iter().all(|r| r.chars().nth(*i).unwrap() == '.'))
-        .map(|(i, _)| i)
-        .collect()
+        .fold((0, 0), |(sum, prefix), (i, x)| {
+            (sum + x * i - prefix, prefix + x)
+        })
+        .0
 }
 
-fn find_empty_rows(input: Vec<String>) -> Vec<usize> {
+fn manhatten_distance(a: (usize, usize), b: (usize, usize)) -> usize {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+fn find_galaxies(input: &Grid<u8>) -> Vec<(usize, usize)> {
     input
         .iter()
-        .enumerate()
-        .filter(|(_, r)| r.chars().all(|c| c == '.'))
-        .map(|(i, _)| i)
+        .filter(|(_, x)| **x == b'#')
+        .map(|(pos, _)| pos)
+        .collect()
+}
+
+fn find_empty_cols(input: &Grid<u8>) -> Vec<usize> {
+    (0..input.cols())
+        .filter(|c| input.col(*c).all(|x| *x == b'.'))
+        .collect()
+}
+
+fn find_empty_rows(input: &Grid<u8>) -> Vec<usize> {
+    (0..input.rows())
+        .filter(|r| input.row(*r).iter().all(|x| *x == b'.'))
         .collect()
 }
 
 #[test]
 fn should_find_empty_rows() {
-    let input = vec![
-        "...#......".to_string(),
-        ".......#..".to_string(),
-        "#.........".to_string(),
-        "..........".to_string(),
-        "......#...".to_string(),
-        ".#........".to_string(),
-        ".........#".to_string(),
-        "..........".to_string(),
-        ".......#..".to_string(),
-        "#...#.....".to_string(),
-    ];
-
-    let empty_rows = find_empty_rows(input);
+    let input = Grid::parse(&[
+        "...#......",
+        ".......#..",
+        "#.........",
+        "..........",
+        "......#...",
+        ".#........",
+        ".........#",
+        "..........",
+        ".......#..",
+        "#...#.....",
+    ]);
+
+    let empty_rows = find_empty_rows(&input);
 
     assert_eq!(empty_rows, vec![3, 7]);
 }
 
 #[test]
 fn should_find_empty_cols() {
-    let input = vec![
-        "...#......".to_string(),
-        ".......#..".to_string(),
-        "#.........".to_string(),
-        "..........".to_string(),
-        "......#...".to_string(),
-        ".#........".to_string(),
-        ".........#".to_string(),
-        "..........".to_string(),
-        ".......#..".to_string(),
-        "#...#.....".to_string(),
-    ];
-
-    let empty_cols = find_empty_cols(input);
+    let input = Grid::parse(&[
+        "...#......",
+        ".......#..",
+        "#.........",
+        "..........",
+        "......#...",
+        ".#........",
+        ".........#",
+        "..........",
+        ".......#..",
+        "#...#.....",
+    ]);
+
+    let empty_cols = find_empty_cols(&input);
 
     assert_eq!(empty_cols, vec![2, 5, 8]);
 }
 
 #[test]
 fn should_find_galaxies() {
-    let expanded_universe = vec![
-        "....#........".to_string(),
-        ".........#...".to_string(),
-        "#............".to_string(),
-        ".............".to_string(),
-        ".............".to_string(),
-        "........#....".to_string(),
-        ".#...........".to_string(),
-        "............#".to_string(),
-        ".............".to_string(),
-        ".............".to_string(),
-        ".........#...".to_string(),
-        "#....#.......".to_string(),
-    ];
-
-    let galaxies = find_galaxies(expanded_universe);
+    let expanded_universe = Grid::parse(&[
+        "....#........",
+        ".........#...",
+        "#............",
+        ".............",
+        ".............",
+        "........#....",
+        ".#...........",
+        "............#",
+        ".............",
+        ".............",
+        ".........#...",
+        "#....#.......",
+    ]);
+
+    let galaxies = find_galaxies(&expanded_universe);
 
     assert_eq!(
         galaxies,
@@ -151,22 +242,89 @@ fn should_find_galaxies() {
 
 #[test]
 fn should_find_shortest_distance_between_galaxies() {
-    let expanded_universe = vec![
-        "....#........".to_string(),
-        ".........#...".to_string(),
-        "#............".to_string(),
-        ".............".to_string(),
-        ".............".to_string(),
-        "........#....".to_string(),
-        ".#...........".to_string(),
-        "............#".to_string(),
-        ".............".to_string(),
-        ".............".to_string(),
-        ".........#...".to_string(),
-        "#....#.......".to_string(),
-    ];
-
-    let shortest_distance = find_shortest_path_between_galaxies(expanded_universe, 2);
+    let expanded_universe = Grid::parse(&[
+        "....#........",
+        ".........#...",
+        "#............",
+        ".............",
+        ".............",
+        "........#....",
+        ".#...........",
+        "............#",
+        ".............",
+        ".............",
+        ".........#...",
+        "#....#.......",
+    ]);
+
+    let shortest_distance = find_shortest_path_between_galaxies(&expanded_universe, 2);
 
     assert_eq!(shortest_distance, 702);
 }
+
+#[test]
+fn should_expand_galaxy_coordinates() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    let galaxies = expanded_galaxies(input, 2);
+
+    assert_eq!(
+        galaxies,
+        vec![
+            (0, 4),
+            (1, 9),
+            (2, 0),
+            (5, 8),
+            (6, 1),
+            (7, 12),
+            (10, 9),
+            (11, 0),
+            (11, 5),
+        ]
+    );
+}
+
+#[test]
+fn should_sum_distances_for_any_factor() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    assert_eq!(sum_of_shortest_path_between_galaxies(input.clone(), 2), 374);
+    assert_eq!(
+        sum_of_shortest_path_between_galaxies(input.clone(), 10),
+        1030
+    );
+    assert_eq!(sum_of_shortest_path_between_galaxies(input, 100), 8410);
+}
+
+#[test]
+#[should_panic(expected = "expansion factor should be at least 1")]
+fn should_reject_expansion_factor_of_0() {
+    sum_of_shortest_path_between_galaxies(vec!["#.#"], 0);
+}
+
+#[test]
+fn should_answer_distance_queries() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    let universe = Universe::new(input, 2);
+
+    assert_eq!(universe.len(), 9);
+    assert_eq!(universe.galaxy(1), Some((0, 4)));
+    assert_eq!(universe.distance(5, 9), Some(9));
+    assert_eq!(universe.distance(1, 7), Some(15));
+    assert_eq!(universe.distance(3, 6), Some(17));
+    assert_eq!(universe.distance(8, 9), Some(5));
+    assert_eq!(universe.distance(0, 1), None);
+    assert_eq!(universe.distance(1, 10), None);
+    assert_eq!(universe.nearest(8, 2), vec![(9, 5), (5, 6)]);
+}
+
+#[test]
+fn should_export_distance_matrix_as_csv() {
+    let universe = Universe::new(vec!["#.#", "...", "..#"], 3);
+
+    assert_eq!(
+        universe.distance_matrix_csv(),
+        "galaxy,1,2,3\n1,0,4,8\n2,4,0,4\n3,8,4,0\n"
+    );
+}
@@ -1,115 +1,230 @@
-use std::{collections::HashMap, iter::repeat};
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RowError {
+    MissingGroups(String),
+    InvalidSpring(char),
+    InvalidGroup(String),
+    ZeroFactor,
+}
 
-pub fn arrangements(input: Vec<&str>) -> u64 {
-    let cache = &mut HashMap::<(String, Vec<u32>), u64>::new();
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Row {
+    springs: Vec<u8>,
+    groups: Vec<usize>,
+}
 
-    input
-        .iter()
-        .map(|x| x.split(' '))
-        .map(|mut x| {
-            (
-                x.next().unwrap().to_string(),
-                x.next()
-                    .unwrap()
-                    .split(',')
-                    .map(|x| x.parse::<u32>().unwrap())
-                    .collect::<Vec<u32>>(),
-            )
-        })
-        .map(|(input, groups)| count_arrangements(input, groups, cache))
-        .sum()
+#[derive(Debug)]
+pub struct Arrangements<'a> {
+    row: &'a Row,
+    dots: Vec<usize>,
+    table: Vec<u64>,
+    stack: Vec<(usize, usize, Vec<u8>)>,
 }
 
-pub fn arrangements_five(input: Vec<&str>) -> u64 {
-    let cache = &mut HashMap::<(String, Vec<u32>), u64>::new();
+pub fn arrangements(input: Vec<&str>) -> Result<u64, RowError> {
+    arrangements_unfolded(input, 1)
+}
+
+pub fn arrangements_five(input: Vec<&str>) -> Result<u64, RowError> {
+    arrangements_unfolded(input, 5)
+}
+
+// Unfolding by zero would leave every row empty, so it is rejected.
+pub fn arrangements_unfolded(input: Vec<&str>, factor: usize) -> Result<u64, RowError> {
+    if factor == 0 {
+        return Err(RowError::ZeroFactor);
+    }
 
     input
         .iter()
-        .map(|x| x.split(' '))
-        .map(|mut x| {
-            (
-                repeat(x.next().unwrap().to_string())
-                    .take(5)
-                    .collect::<Vec<String>>()
-                    .join("?"),
-                x.next()
-                    .unwrap()
-                    .split(',')
-                    .map(|x| x.parse::<u32>().unwrap())
-                    .collect::<Vec<u32>>()
-                    .repeat(5),
-            )
-        })
-        .map(|(input, groups)| count_arrangements(input, groups, cache))
+        .map(|x| Ok(Row::parse(x)?.unfold(factor).count()))
         .sum()
 }
 
-fn count_arrangements(
-    input: String,
-    groups: Vec<u32>,
-    cache: &mut HashMap<(String, Vec<u32>), u64>,
-) -> u64 {
-    if input.is_empty() {
-        return match groups.is_empty() {
-            true => 1,
-            false => 0,
-        };
+impl Row {
+    pub fn parse(input: &str) -> Result<Self, RowError> {
+        let (springs, groups) = input
+            .split_once(' ')
+            .ok_or_else(|| RowError::MissingGroups(input.to_string()))?;
+
+        if let Some(x) = springs.chars().find(|x| !matches!(x, '.' | '#' | '?')) {
+            return Err(RowError::InvalidSpring(x));
+        }
+
+        Ok(Self {
+            springs: springs.bytes().collect(),
+            groups: groups
+                .split(',')
+                .map(|x| {
+                    x.parse::<usize>()
+                        .map_err(|_| RowError::InvalidGroup(x.to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+        })
     }
 
-    if groups.is_empty() {
-        return match input.contains('#') {
-            true => 0,
-            false => 1,
-        };
+    pub fn unfold(&self, factor: usize) -> Self {
+        Self {
+            springs: vec![self.springs.clone(); factor].join(&b'?'),
+            groups: self.groups.repeat(factor),
+        }
     }
 
-    let head_group = *groups.iter().nth(0).unwrap();
-    let head_input = input.chars().next().unwrap();
-    let input_len = input.len() as u32;
+    pub fn count(&self) -> u64 {
+        self.table()[0]
+    }
 
-    if cache.contains_key(&(input.clone(), groups.clone())) {
-        return *cache.get(&(input, groups)).unwrap();
+    pub fn arrangements(&self) -> Arrangements<'_> {
+        let table = self.table();
+        let stack = match table[0] {
+            0 => vec![],
+            _ => vec![(0, 0, vec![])],
+        };
+
+        Arrangements {
+            row: self,
+            dots: self.dots(),
+            table,
+            stack,
+        }
     }
 
-    let mut result = 0_u64;
+    // table[i * (groups + 1) + j] holds the number of ways to place
+    // groups[j..] into springs[i..].
+    fn table(&self) -> Vec<u64> {
+        let n = self.springs.len();
+        let m = self.groups.len();
+        let width = m + 1;
+        let dots = self.dots();
+
+        let mut table = vec![0; (n + 1) * width];
+        table[n * width + m] = 1;
+
+        for i in (0..n).rev() {
+            for j in 0..=m {
+                let mut ways = 0;
+
+                if self.springs[i] != b'#' {
+                    ways += table[(i + 1) * width + j];
+                }
+
+                if let Some(end) = self.group_end(i, j, &dots) {
+                    ways += table[end * width + j + 1];
+                }
 
-    if head_input == '.' || head_input == '?' {
-        result += count_arrangements(input[1..].to_string(), groups.clone(), cache);
+                table[i * width + j] = ways;
+            }
+        }
+
+        table
     }
 
-    if (head_input == '#' || head_input == '?')
-        && head_group <= input_len
-        && !input.chars().take(head_group as usize).any(|x| x == '.')
-        && (head_group == input_len || input.chars().nth(head_group as usize).unwrap() != '#')
-    {
-        result += count_arrangements(
-            input.chars().skip(head_group as usize + 1).collect(),
-            groups.clone().into_iter().skip(1).collect(),
-            cache,
-        );
+    fn dots(&self) -> Vec<usize> {
+        let mut dots = vec![0; self.springs.len() + 1];
+
+        for (i, spring) in self.springs.iter().enumerate() {
+            dots[i + 1] = dots[i] + usize::from(*spring == b'.');
+        }
+
+        dots
     }
 
-    cache.insert((input, groups), result);
+    fn group_end(&self, i: usize, j: usize, dots: &[usize]) -> Option<usize> {
+        let n = self.springs.len();
+        let group = *self.groups.get(j)?;
 
-    result
+        let fits = self.springs[i] != b'.'
+            && i + group <= n
+            && dots[i + group] == dots[i]
+            && (i + group == n || self.springs[i + group] != b'#');
+
+        fits.then_some((i + group + 1).min(n))
+    }
+}
+
+impl Iterator for Arrangements<'_> {
+    type Item = String;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let n = self.row.springs.len();
+        let width = self.row.groups.len() + 1;
+
+        while let Some((i, j, prefix)) = self.stack.pop() {
+            if i == n {
+                return Some(String::from_utf8(prefix).unwrap());
+            }
+
+            if self.row.springs[i] != b'#' && self.table[(i + 1) * width + j] > 0 {
+                let mut operational = prefix.clone();
+                operational.push(b'.');
+                self.stack.push((i + 1, j, operational));
+            }
+
+            if let Some(end) = self.row.group_end(i, j, &self.dots) {
+                if self.table[end * width + j + 1] > 0 {
+                    let mut damaged = prefix;
+                    damaged.extend(std::iter::repeat_n(b'#', self.row.groups[j]));
+                    if end > i + self.row.groups[j] {
+                        damaged.push(b'.');
+                    }
+                    self.stack.push((end, j + 1, damaged));
+                }
+            }
+        }
+
+        None
+    }
 }
 
 #[test]
 fn should_count_arrangements() {
-    let input = ".??..??...?##.";
-    let groups = vec![1, 1, 3];
+    let input = ".??..??...?##. 1,1,3";
 
-    let arrangements = count_arrangements(input.to_string(), groups, &mut HashMap::new());
+    let arrangements = Row::parse(input).unwrap().count();
 
     assert_eq!(arrangements, 4);
 }
 
 #[test]
 fn should_count_arrangements_for_large_example() {
-    let input = "?###????????";
-    let groups = vec![3, 2, 1];
+    let input = "?###???????? 3,2,1";
 
-    let arrangements = count_arrangements(input.to_string(), groups, &mut HashMap::new());
+    let arrangements = Row::parse(input).unwrap().count();
 
     assert_eq!(arrangements, 10);
 }
+
+#[test]
+fn should_count_arrangements_with_unfold_factor() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    assert_eq!(arrangements_unfolded(input.clone(), 1), Ok(21));
+    assert_eq!(arrangements_unfolded(input.clone(), 5), Ok(525152));
+    assert_eq!(arrangements_unfolded(input, 0), Err(RowError::ZeroFactor));
+}
+
+#[test]
+fn should_enumerate_arrangements() {
+    let row = Row::parse("???.### 1,1,3").unwrap();
+
+    assert_eq!(row.arrangements().collect::<Vec<_>>(), vec!["#.#.###"]);
+
+    for line in include_str!("sample.txt").lines() {
+        let row = Row::parse(line).unwrap();
+
+        assert_eq!(row.arrangements().count() as u64, row.count());
+        assert!(row.arrangements().all(|x| x.len() == row.springs.len()));
+    }
+}
+
+#[test]
+fn should_reject_malformed_rows() {
+    assert_eq!(
+        Row::parse("???.###"),
+        Err(RowError::MissingGroups(String::from("???.###")))
+    );
+    assert_eq!(Row::parse("??x 1"), Err(RowError::InvalidSpring('x')));
+    assert_eq!(
+        Row::parse("??? 1,a"),
+        Err(RowError::InvalidGroup(String::from("a")))
+    );
+}
@@ -1,3 +1,28 @@
+use grid::Grid;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Axis {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Reflection {
+    pub axis: Axis,
+    pub position: u32,
+    pub mismatches: Vec<(usize, usize)>,
+}
+
+// Every row and column is a bitset of its rocks, split into 64-bit words so
+// patterns of any size fit.
+type Line = Vec<u64>;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Pattern {
+    rows: Vec<Line>,
+    cols: Vec<Line>,
+}
+
 pub fn notes(input: Vec<&str>, smudge: u32) -> u32 {
     input
         .split(|x| x.is_empty())
@@ -6,71 +31,113 @@ pub fn notes(input: Vec<&str>, smudge: u32) -> u32 {
         .sum()
 }
 
-fn calc_reflection(input: Vec<&str>, smudge: u32) -> u32 {
-    let vert = reflection(transpose(&input), smudge);
-    let hori = reflection(input.iter().map(|x| x.to_string()).collect(), smudge);
-
-    if hori > 0 {
-        return hori * 100;
-    }
-
-    vert + hori * 100
+pub fn patterns(input: Vec<&str>) -> Vec<Pattern> {
+    input
+        .split(|x| x.is_empty())
+        .map(|x| Pattern::new(x.to_vec()))
+        .collect()
 }
 
-fn reflection(input: Vec<String>, smudge: u32) -> u32 {
-    let mirror_pos = find_mirror(input.iter().map(|x| x.to_string()).collect(), smudge);
+impl Pattern {
+    pub fn new(input: Vec<&str>) -> Self {
+        let grid = Grid::parse(&input);
 
-    for m_pos in mirror_pos.iter() {
-        let top = (0..*m_pos + 1)
-            .rev()
-            .map(|x| input.get(x as usize).unwrap().chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
+        let rows = (0..grid.rows())
+            .map(|r| bits(grid.cols(), grid.row(r).iter()))
+            .collect();
+        let cols = (0..grid.cols())
+            .map(|c| bits(grid.rows(), grid.col(c)))
+            .collect();
 
-        let bottom = (m_pos + 1..input.len() as u32)
-            .map(|x| input.get(x as usize).unwrap().chars().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
+        Self { rows, cols }
+    }
 
-        let is_mirror = top
-            .iter()
-            .zip(bottom.iter())
-            .filter(|(l, r)| l != r)
-            .count()
-            == smudge as usize;
+    pub fn reflections(&self) -> Vec<Reflection> {
+        let horizontal = axes(&self.rows)
+            .into_iter()
+            .map(|(position, cells)| Reflection {
+                axis: Axis::Horizontal,
+                position,
+                mismatches: cells,
+            });
+
+        let vertical = axes(&self.cols)
+            .into_iter()
+            .map(|(position, cells)| Reflection {
+                axis: Axis::Vertical,
+                position,
+                mismatches: cells.into_iter().map(|(c, r)| (r, c)).collect(),
+            });
+
+        horizontal.chain(vertical).collect()
+    }
 
-        if is_mirror {
-            return top.len() as u32;
-        }
+    pub fn mirror(&self, smudge: u32) -> Option<Reflection> {
+        self.reflections()
+            .into_iter()
+            .find(|x| x.mismatches.len() == smudge as usize)
     }
 
-    0
-}
+    pub fn smudge(&self) -> Option<(usize, usize)> {
+        self.mirror(1).map(|x| x.mismatches[0])
+    }
 
-fn find_mirror(input: Vec<String>, smudge: u32) -> Vec<u32> {
-    input
-        .iter()
-        .zip(input.iter().skip(1))
-        .enumerate()
-        .filter(|(_, (l, r))| difference(l, r) <= smudge)
-        .map(|(i, _)| i as u32)
-        .collect()
+    pub fn score(&self, smudge: u32) -> u32 {
+        match self.mirror(smudge) {
+            Some(Reflection {
+                axis: Axis::Horizontal,
+                position,
+                ..
+            }) => position * 100,
+            Some(Reflection {
+                axis: Axis::Vertical,
+                position,
+                ..
+            }) => position,
+            None => 0,
+        }
+    }
 }
 
-fn difference(l: &str, r: &str) -> u32 {
-    l.chars().zip(r.chars()).filter(|(l, r)| l != r).count() as u32
+fn calc_reflection(input: Vec<&str>, smudge: u32) -> u32 {
+    Pattern::new(input).score(smudge)
 }
 
-fn transpose(input: &Vec<&str>) -> Vec<String> {
-    let mut result = vec![];
+fn bits<'a>(len: usize, line: impl Iterator<Item = &'a u8>) -> Line {
+    let mut words = vec![0; len.div_ceil(64)];
 
-    for i in 0..input[0].len() {
-        let line = input
-            .iter()
-            .map(|x| x.chars().nth(i).unwrap())
-            .collect::<String>();
-        result.push(line);
+    for (i, _) in line.enumerate().filter(|(_, x)| **x == b'#') {
+        words[i / 64] |= 1 << (i % 64);
     }
 
-    result
+    words
+}
+
+fn axes(lines: &[Line]) -> Vec<(u32, Vec<(usize, usize)>)> {
+    (1..lines.len())
+        .map(|position| {
+            let cells = (0..position.min(lines.len() - position))
+                .flat_map(|k| {
+                    let line = position - 1 - k;
+
+                    lines[line]
+                        .iter()
+                        .zip(&lines[position + k])
+                        .enumerate()
+                        .flat_map(|(word, (a, b))| {
+                            let diff = a ^ b;
+
+                            (0..64)
+                                .filter(move |bit| diff & 1 << bit != 0)
+                                .map(move |bit| word * 64 + bit)
+                        })
+                        .map(move |bit| (line, bit))
+                })
+                .collect();
+
+            (position as u32, cells)
+        })
+        .collect()
 }
 
 #[test]
@@ -106,3 +173,52 @@ fn should_count_notes_with_smudge_of_1() {
 
     assert_eq!(count, 300);
 }
+
+#[test]
+fn should_report_every_axis_with_its_mismatches() {
+    let patterns = patterns(include_str!("sample.txt").lines().collect());
+
+    let reflections = patterns[0].reflections();
+
+    assert_eq!(reflections.len(), 6 + 8);
+    assert_eq!(
+        reflections
+            .iter()
+            .filter(|x| x.mismatches.is_empty())
+            .collect::<Vec<_>>(),
+        vec![&Reflection {
+            axis: Axis::Vertical,
+            position: 5,
+            mismatches: vec![],
+        }]
+    );
+    assert_eq!(patterns[1].mirror(0).map(|x| x.position), Some(4));
+}
+
+#[test]
+fn should_locate_smudge() {
+    let patterns = patterns(include_str!("sample.txt").lines().collect());
+
+    assert_eq!(patterns[0].smudge(), Some((0, 0)));
+    assert_eq!(patterns[1].smudge(), Some((0, 4)));
+}
+
+#[test]
+fn should_handle_patterns_wider_than_64_cells() {
+    let row = format!("{}#.{}", ".".repeat(70), ".".repeat(70));
+    let mirrored = format!("{}.#{}", ".".repeat(70), ".".repeat(70));
+    let input = vec![
+        row.as_str(),
+        mirrored.as_str(),
+        mirrored.as_str(),
+        row.as_str(),
+    ];
+
+    let pattern = Pattern::new(input);
+
+    assert_eq!(pattern.score(0), 200);
+    assert_eq!(
+        pattern.mirror(2).map(|x| x.mismatches),
+        Some(vec![(0, 70), (0, 71)])
+    );
+}
@@ -1,120 +1,136 @@
-pub fn tilt_cycle(input: Vec<&str>, times: usize) -> u64 {
-    let input = input.iter().map(|x| x.to_string()).collect::<Vec<String>>();
+use cycle_detection::History;
+use grid::{
+    image::{Image, Rgb},
+    Dir, Grid, Pos,
+};
 
-    let mut seen = vec![input.clone()];
-    let mut result = input;
-    let mut iter = 0;
+const SPIN: [Dir; 4] = [Dir::North, Dir::West, Dir::South, Dir::East];
 
-    loop {
-        result = cycle(result);
-        iter += 1;
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Platform {
+    cells: Grid<u8>,
+}
 
-        if seen.contains(&result) {
-            break;
+impl Platform {
+    pub fn new(input: Vec<&str>) -> Self {
+        Self {
+            cells: Grid::parse(&input),
         }
-
-        seen.push(result.clone());
     }
 
-    let first = seen.iter().position(|x| x == &result).unwrap();
-
-    load(seen[(times - first) % (iter - first) + first].clone())
-}
-
-pub fn count_north(input: Vec<&str>) -> u64 {
-    let input = input.iter().map(|x| x.to_string()).collect::<Vec<String>>();
-    let tilted_north = tilt_north(input);
-
-    load(tilted_north)
-}
+    pub fn tilt(&mut self, direction: Dir) {
+        let (lines, length) = match direction {
+            Dir::North | Dir::South => (self.cells.cols(), self.cells.rows()),
+            Dir::West | Dir::East => (self.cells.rows(), self.cells.cols()),
+        };
+
+        for line in 0..lines {
+            let mut free = 0;
+
+            for k in 0..length {
+                let pos = self.pos(direction, line, k);
+
+                match self.cells[pos] {
+                    b'#' => free = k + 1,
+                    b'O' => {
+                        self.cells[pos] = b'.';
+                        let target = self.pos(direction, line, free);
+                        self.cells[target] = b'O';
+                        free += 1;
+                    }
+                    _ => {}
+                }
+            }
+        }
+    }
 
-fn load(input: Vec<String>) -> u64 {
-    let row_numbers = (1..input.len() + 1).rev().collect::<Vec<usize>>();
+    pub fn spin(&mut self) {
+        for direction in SPIN {
+            self.tilt(direction);
+        }
+    }
 
-    row_numbers
-        .iter()
-        .zip(input.iter())
-        .map(|(line_number, line)| {
-            let os = line.chars().filter(|&x| x == 'O').count();
-            line_number * os
-        })
-        .sum::<usize>() as u64
-}
+    pub fn load(&self) -> u64 {
+        let rows = self.cells.rows();
 
-fn cycle(input: Vec<String>) -> Vec<String> {
-    let mut result = input;
+        (0..rows)
+            .map(|r| {
+                let rocks = self.cells.row(r).iter().filter(|&&x| x == b'O').count();
+                ((rows - r) * rocks) as u64
+            })
+            .sum()
+    }
 
-    for _ in 0..4 {
-        result = rotate_90(tilt_north(result));
+    pub fn render(&self) -> Image {
+        Image::render(&self.cells, palette)
     }
 
-    result
+    fn pos(&self, direction: Dir, line: usize, k: usize) -> Pos {
+        match direction {
+            Dir::North => (k, line),
+            Dir::South => (self.cells.rows() - 1 - k, line),
+            Dir::West => (line, k),
+            Dir::East => (line, self.cells.cols() - 1 - k),
+        }
+    }
 }
 
-fn tilt_north(input: Vec<String>) -> Vec<String> {
-    let rotated = transpose(input.iter().map(|x| x.to_string()).collect::<Vec<String>>());
+// Rocks that ended up on a tile they did not start on are highlighted.
+pub fn render_tilt(input: Vec<&str>, direction: Dir) -> Image {
+    let before = Platform::new(input);
+    let mut after = before.clone();
+    after.tilt(direction);
 
-    let rolled = rotated
+    let moved = after
+        .cells
         .iter()
-        .map(|x| roll_left(x.to_string()))
-        .collect::<Vec<String>>();
+        .filter(|(pos, x)| **x == b'O' && before.cells[*pos] != b'O')
+        .map(|(pos, _)| pos)
+        .collect::<Vec<Pos>>();
 
-    transpose(rolled)
+    after.render().overlay(moved, [255, 96, 0])
 }
 
-fn roll_left(line: String) -> String {
-    let split_by_rock = line.split('#').collect::<Vec<&str>>();
-
-    let move_os_left = split_by_rock
-        .iter()
-        .map(|x| {
-            let os = x.chars().filter(|&x| x == 'O').collect::<String>();
-            let dots = x.chars().filter(|&x| x == '.').collect::<String>();
-
-            os + &dots
-        })
-        .collect::<Vec<String>>()
-        .join("#");
+fn palette(tile: &u8) -> Rgb {
+    match tile {
+        b'#' => [64, 64, 64],
+        b'O' => [220, 200, 160],
+        _ => [16, 16, 32],
+    }
+}
 
-    move_os_left
+pub fn spins(platform: &Platform) -> impl Iterator<Item = Platform> + Clone {
+    std::iter::successors(Some(platform.clone()), |current| {
+        let mut next = current.clone();
+        next.spin();
+        Some(next)
+    })
 }
 
-fn rotate_90(input: Vec<String>) -> Vec<String> {
-    let mut result = vec![];
+pub fn find_spin_cycle(platform: &Platform) -> History<Platform> {
+    cycle_detection::hashed(spins(platform)).expect("spinning never ends")
+}
 
-    for i in 0..input[0].len() {
-        let line = input
-            .iter()
-            .map(|x| x.chars().nth(i).unwrap())
-            .rev()
-            .collect::<String>();
-        result.push(line);
-    }
+pub fn tilt_cycle(input: Vec<&str>, times: usize) -> u64 {
+    let platform = Platform::new(input);
 
-    result
+    find_spin_cycle(&platform).state_at(times).load()
 }
 
-fn transpose(input: Vec<String>) -> Vec<String> {
-    let mut result = vec![];
-
-    for i in 0..input[0].len() {
-        let line = input
-            .iter()
-            .map(|x| x.chars().nth(i).unwrap())
-            .collect::<String>();
-        result.push(line);
-    }
+pub fn count_north(input: Vec<&str>) -> u64 {
+    let mut platform = Platform::new(input);
+    platform.tilt(Dir::North);
 
-    result
+    platform.load()
 }
 
 #[test]
 fn should_roll_left() {
-    let input = "OO.#O....O".to_string();
+    let mut platform = Platform::new(vec!["OO.#O....O"]);
 
-    let rolled = roll_left(input);
+    platform.tilt(Dir::West);
 
-    assert_eq!(rolled, "OO.#OO....".to_string());
+    assert_eq!(platform, Platform::new(vec!["OO.#OO...."]));
 }
 
 #[test]
@@ -136,3 +152,46 @@ fn should_tilt_towards_north() {
 
     assert_eq!(result, 136);
 }
+
+#[test]
+fn should_tilt_platform_in_every_direction() {
+    let mut platform = Platform::new(include_str!("sample.txt").lines().collect());
+
+    platform.spin();
+
+    let expected = Platform::new(vec![
+        ".....#....",
+        "....#...O#",
+        "...OO##...",
+        ".OO#......",
+        ".....OOO#.",
+        ".O#...O#.#",
+        "....O#....",
+        "......OOOO",
+        "#...O###..",
+        "#..OO#....",
+    ]);
+
+    assert_eq!(platform, expected);
+}
+
+#[test]
+fn should_find_spin_cycle_start_and_period() {
+    let platform = Platform::new(include_str!("sample.txt").lines().collect());
+
+    let history = find_spin_cycle(&platform);
+
+    assert_eq!((history.cycle.mu, history.cycle.lambda), (3, 7));
+    assert_eq!(history.state_at(1_000_000_000).load(), 64);
+}
+
+#[test]
+fn should_render_moved_rocks() {
+    let image = render_tilt(vec!["O.O#.", "..O.O"], Dir::West);
+
+    assert_eq!(image.pixel((0, 0)), Some([220, 200, 160]));
+    assert_eq!(image.pixel((0, 1)), Some([237, 148, 80]));
+    assert_eq!(image.pixel((0, 2)), Some([16, 16, 32]));
+    assert_eq!(image.pixel((0, 3)), Some([64, 64, 64]));
+    assert_eq!(image.pixel((1, 0)), Some([237, 148, 80]));
+}
@@ -0,0 +1,371 @@
+use std::{collections::HashMap, ops::Index};
+
+use crate::hash_from;
+
+// Keys are spread over 256 buckets by the HASH algorithm and every bucket keeps
+// its entries in insertion order. An index from key to slot makes lookups
+// amortized O(1): updating a key keeps its slot, removing a key leaves an empty
+// slot behind, and a bucket is compacted once more than half of it is empty.
+#[derive(Debug, Clone)]
+pub struct HolidayHashMap<K, V> {
+    buckets: Vec<Vec<Option<(K, V)>>>,
+    live: Vec<usize>,
+    index: HashMap<String, usize>,
+}
+
+type Slots<'a, K, V> = std::iter::Flatten<std::slice::Iter<'a, Vec<Option<(K, V)>>>>;
+
+pub struct Iter<'a, K, V> {
+    inner: std::iter::Flatten<Slots<'a, K, V>>,
+}
+
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    entry: &'a mut (K, V),
+}
+
+pub struct VacantEntry<'a, K, V> {
+    bucket: &'a mut Vec<Option<(K, V)>>,
+    live: &'a mut usize,
+    index: &'a mut HashMap<String, usize>,
+    key: K,
+}
+
+impl<K: AsRef<str>, V> HolidayHashMap<K, V> {
+    pub fn new() -> Self {
+        Self {
+            buckets: (0..256).map(|_| Vec::new()).collect(),
+            live: vec![0; 256],
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.entry(key) {
+            Entry::Occupied(mut entry) => Some(entry.insert(value)),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                None
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let slot = self.index.remove(key)?;
+        let hash = hash_from(key) as usize;
+        let bucket = &mut self.buckets[hash];
+
+        let (_, value) = bucket[slot].take()?;
+        self.live[hash] -= 1;
+
+        if self.live[hash] * 2 < bucket.len() {
+            bucket.retain(Option::is_some);
+
+            for (slot, (key, _)) in bucket.iter().flatten().enumerate() {
+                *self.index.get_mut(key.as_ref()).unwrap() = slot;
+            }
+        }
+
+        Some(value)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&V> {
+        let slot = self.index.get(key)?;
+
+        self.buckets[hash_from(key) as usize][*slot]
+            .as_ref()
+            .map(|(_, v)| v)
+    }
+
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let slot = self.index.get(key)?;
+
+        self.buckets[hash_from(key) as usize][*slot]
+            .as_mut()
+            .map(|(_, v)| v)
+    }
+
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let hash = hash_from(key.as_ref()) as usize;
+        let bucket = &mut self.buckets[hash];
+
+        match self.index.get(key.as_ref()).copied() {
+            Some(slot) => Entry::Occupied(OccupiedEntry {
+                entry: bucket[slot].as_mut().expect("indexed slots should be live"),
+            }),
+            None => Entry::Vacant(VacantEntry {
+                bucket,
+                live: &mut self.live[hash],
+                index: &mut self.index,
+                key,
+            }),
+        }
+    }
+
+    pub fn bucket(&self, index: u8) -> Iter<'_, K, V> {
+        Iter {
+            inner: std::slice::from_ref(&self.buckets[index as usize])
+                .iter()
+                .flatten()
+                .flatten(),
+        }
+    }
+
+    pub fn buckets(&self) -> impl Iterator<Item = (u8, Iter<'_, K, V>)> {
+        (0..=u8::MAX)
+            .filter(|i| self.live[*i as usize] > 0)
+            .map(|i| (i, self.bucket(i)))
+    }
+
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        Iter {
+            inner: self.buckets.iter().flatten().flatten(),
+        }
+    }
+}
+
+impl<'a, K: AsRef<str>, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => &entry.entry.0,
+            Entry::Vacant(entry) => &entry.key,
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with(self, default: impl FnOnce() -> V) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn and_modify(mut self, f: impl FnOnce(&mut V)) -> Self {
+        if let Entry::Occupied(entry) = &mut self {
+            f(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub fn get(&self) -> &V {
+        &self.entry.1
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.entry.1
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.entry.1
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        std::mem::replace(self.get_mut(), value)
+    }
+}
+
+impl<'a, K: AsRef<str>, V> VacantEntry<'a, K, V> {
+    pub fn insert(self, value: V) -> &'a mut V {
+        self.index
+            .insert(self.key.as_ref().to_string(), self.bucket.len());
+        *self.live += 1;
+        self.bucket.push(Some((self.key, value)));
+
+        &mut self.bucket.last_mut().unwrap().as_mut().unwrap().1
+    }
+}
+
+// Empty slots are an implementation detail, so maps holding the same entries
+// in the same order are equal.
+impl<K: PartialEq, V: PartialEq> PartialEq for HolidayHashMap<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index.len() == other.index.len()
+            && self
+                .buckets
+                .iter()
+                .flatten()
+                .flatten()
+                .eq(other.buckets.iter().flatten().flatten())
+    }
+}
+
+impl<K: Eq, V: Eq> Eq for HolidayHashMap<K, V> {}
+
+impl<K: AsRef<str>, V> Default for HolidayHashMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: AsRef<str>, V> FromIterator<(K, V)> for HolidayHashMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = Self::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: AsRef<str>, V> Extend<(K, V)> for HolidayHashMap<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K, V> IntoIterator for HolidayHashMap<K, V> {
+    type Item = (K, V);
+    type IntoIter = std::iter::Flatten<std::iter::Flatten<std::vec::IntoIter<Vec<Option<(K, V)>>>>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.buckets.into_iter().flatten().flatten()
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a HolidayHashMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        Iter {
+            inner: self.buckets.iter().flatten().flatten(),
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k, v))
+    }
+}
+
+impl<K: AsRef<str>, V> Index<&str> for HolidayHashMap<K, V> {
+    type Output = V;
+
+    fn index(&self, key: &str) -> &V {
+        self.get(key).expect("key should be in the map")
+    }
+}
+
+#[test]
+fn should_reproduce_focusing_power() {
+    let input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    let mut map = HolidayHashMap::new();
+
+    for step in input.split(',') {
+        match step.split_once('=') {
+            Some((label, focal)) => {
+                map.insert(label, focal.parse::<u64>().unwrap());
+            }
+            None => {
+                map.remove(step.trim_end_matches('-'));
+            }
+        }
+    }
+
+    let power = map
+        .buckets()
+        .flat_map(|(i, lenses)| {
+            lenses
+                .enumerate()
+                .map(move |(slot, (_, focal))| (i as u64 + 1) * (slot as u64 + 1) * focal)
+        })
+        .sum::<u64>();
+
+    assert_eq!(power, 145);
+    assert_eq!(Ok(power), crate::focusing_power(input));
+    assert_eq!(map["ot"], 7);
+}
+
+#[test]
+fn should_keep_insertion_order_per_bucket() {
+    let mut map = [("rn", 1), ("cm", 2), ("qp", 3)]
+        .into_iter()
+        .collect::<HolidayHashMap<&str, u32>>();
+
+    map.extend([("rn", 4), ("pc", 5)]);
+
+    assert_eq!(map.len(), 4);
+    assert_eq!(
+        map.bucket(0).collect::<Vec<_>>(),
+        vec![(&"rn", &4), (&"cm", &2)]
+    );
+    assert_eq!(
+        map.into_iter().collect::<Vec<_>>(),
+        vec![("rn", 4), ("cm", 2), ("qp", 3), ("pc", 5)]
+    );
+}
+
+#[test]
+fn should_update_through_entries() {
+    let mut map = HolidayHashMap::<String, u32>::new();
+
+    *map.entry(String::from("ab")).or_insert(0) += 1;
+    map.entry(String::from("ab"))
+        .and_modify(|x| *x *= 10)
+        .or_insert(0);
+    map.entry(String::from("cd"))
+        .and_modify(|x| *x *= 10)
+        .or_insert(3);
+
+    assert_eq!(map.get("ab"), Some(&10));
+    assert_eq!(map["cd"], 3);
+    assert_eq!(map.remove("ab"), Some(10));
+    assert!(!map.contains_key("ab"));
+    assert_eq!(
+        (&map).into_iter().collect::<Vec<_>>(),
+        vec![(&String::from("cd"), &3)]
+    );
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        (&map).into_iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn should_compact_buckets_once_half_empty() {
+    let mut map = ["rn", "cm", "aju", "akd"]
+        .into_iter()
+        .zip(1..)
+        .collect::<HolidayHashMap<&str, u32>>();
+
+    map.remove("rn");
+    map.remove("aju");
+
+    assert_eq!(map.buckets[0].len(), 4);
+    assert_eq!(map, [("cm", 2), ("akd", 4)].into_iter().collect());
+
+    map.remove("akd");
+    map.insert("rn", 5);
+
+    assert_eq!(map.buckets[0].len(), 2);
+    assert_eq!(map.get("cm"), Some(&2));
+    assert_eq!(
+        map.bucket(0).collect::<Vec<_>>(),
+        vec![(&"cm", &2), (&"rn", &5)]
+    );
+}
@@ -1,90 +1,168 @@
-use std::collections::HashMap;
+use std::str::FromStr;
+
+use holiday_map::HolidayHashMap;
+
+pub mod holiday_map;
 
 pub fn hash_sum(input: &str) -> u64 {
     input.replace('\n', "").split(',').map(hash_from).sum()
 }
 
-pub fn focusing_power(input: &str) -> u64 {
-    let focus_map = input.replace('\n', "").split(',').fold(
-        HashMap::<u64, Vec<String>>::new(),
-        |mut acc, curr| {
-            let label = box_index(curr);
-            let box_index = hash_from(label);
-
-            if acc.contains_key(&box_index) {
-                let lenses = acc.get_mut(&box_index).unwrap();
-
-                if curr.contains('-') {
-                    let updated_lenses = remove_lens(lenses.to_vec(), label);
-                    acc.insert(box_index, updated_lenses);
-                } else {
-                    let updated_lenses = update_or_insert(lenses.to_vec(), curr);
-                    acc.insert(box_index, updated_lenses);
-                }
-            } else if curr.contains('=') {
-                acc.insert(box_index, vec![curr.to_string()]);
-            }
-            acc
-        },
-    );
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Step {
+    Insert { label: String, focal: u8 },
+    Remove { label: String },
+}
 
-    calc_power(focus_map)
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum StepError {
+    MissingOperation(String),
+    InvalidFocal(String),
 }
 
-fn calc_power(focus_map: HashMap<u64, Vec<String>>) -> u64 {
-    focus_map
+// The boxes are the buckets of a HolidayHashMap keyed by label, which already
+// hashes labels with HASH, keeps every bucket in insertion order and indexes
+// labels by slot so that insert, update and remove are amortized O(1).
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct LensBoxes {
+    lenses: HolidayHashMap<String, u8>,
+}
+
+pub fn focusing_power(input: &str) -> Result<u64, StepError> {
+    let mut boxes = LensBoxes::new();
+
+    for step in parse_steps(input)? {
+        boxes.apply(&step);
+    }
+
+    Ok(boxes.focusing_power())
+}
+
+pub fn parse_steps(input: &str) -> Result<Vec<Step>, StepError> {
+    split_steps(input).iter().map(|x| x.parse()).collect()
+}
+
+pub fn trace(input: &str) -> Result<Vec<String>, StepError> {
+    let mut boxes = LensBoxes::new();
+
+    split_steps(input)
         .iter()
-        .map(|(key, lenses)| {
-            lenses
-                .iter()
-                .enumerate()
-                .map(|(i, lens)| (key + 1) * (i as u64 + 1) * focus_length(lens))
-                .sum::<u64>()
+        .map(|x| {
+            boxes.apply(&x.parse()?);
+            Ok(format!("After \"{}\":\n{}", x, boxes.dump()))
         })
-        .sum()
+        .collect()
 }
 
-fn focus_length(lens: &str) -> u64 {
-    lens.split('=').nth(1).unwrap().parse::<u64>().unwrap()
+impl FromStr for Step {
+    type Err = StepError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        if let Some(label) = input.strip_suffix('-') {
+            return Ok(Step::Remove {
+                label: label.to_string(),
+            });
+        }
+
+        let (label, focal) = input
+            .split_once('=')
+            .ok_or_else(|| StepError::MissingOperation(input.to_string()))?;
+        let focal = focal
+            .parse::<u8>()
+            .map_err(|_| StepError::InvalidFocal(input.to_string()))?;
+
+        Ok(Step::Insert {
+            label: label.to_string(),
+            focal,
+        })
+    }
 }
 
-fn update_or_insert(mut lenses: Vec<String>, curr: &str) -> Vec<String> {
-    let has_label = lenses
-        .iter()
-        .map(|x| box_index(x))
-        .any(|x| x == box_index(curr));
-
-    if has_label {
-        lenses
-            .iter()
-            .map(|x| {
-                if box_index(x) == box_index(curr) {
-                    curr.to_string()
-                } else {
-                    x.to_string()
-                }
+impl Step {
+    pub fn label(&self) -> &str {
+        match self {
+            Step::Insert { label, .. } | Step::Remove { label } => label,
+        }
+    }
+
+    pub fn box_index(&self) -> usize {
+        hash_from(self.label()) as usize
+    }
+}
+
+impl LensBoxes {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn apply(&mut self, step: &Step) {
+        match step {
+            Step::Insert { label, focal } => self.insert(label, *focal),
+            Step::Remove { label } => {
+                self.remove(label);
+            }
+        }
+    }
+
+    pub fn insert(&mut self, label: &str, focal: u8) {
+        match self.lenses.get_mut(label) {
+            Some(lens) => *lens = focal,
+            None => {
+                self.lenses.insert(label.to_string(), focal);
+            }
+        }
+    }
+
+    pub fn remove(&mut self, label: &str) -> Option<u8> {
+        self.lenses.remove(label)
+    }
+
+    pub fn get(&self, label: &str) -> Option<u8> {
+        self.lenses.get(label).copied()
+    }
+
+    pub fn lenses(&self, box_index: u8) -> impl Iterator<Item = (&str, u8)> {
+        self.lenses
+            .bucket(box_index)
+            .map(|(label, focal)| (label.as_str(), *focal))
+    }
+
+    pub fn focusing_power(&self) -> u64 {
+        self.lenses
+            .buckets()
+            .flat_map(|(i, lenses)| {
+                lenses.enumerate().map(move |(slot, (_, focal))| {
+                    (i as u64 + 1) * (slot as u64 + 1) * *focal as u64
+                })
+            })
+            .sum()
+    }
+
+    pub fn dump(&self) -> String {
+        self.lenses
+            .buckets()
+            .map(|(i, lenses)| {
+                let lenses = lenses
+                    .map(|(label, focal)| format!("[{} {}]", label, focal))
+                    .collect::<Vec<String>>();
+
+                format!("Box {}: {}\n", i, lenses.join(" "))
             })
             .collect()
-    } else {
-        lenses.push(curr.to_string());
-        lenses
     }
 }
 
-fn remove_lens(lenses: Vec<String>, label: &str) -> Vec<String> {
-    lenses
-        .iter()
-        .filter(|x| box_index(x) != label)
-        .map(|x| x.to_string())
+// Line breaks are ignored and so are empty steps, like the one left behind by
+// a trailing comma.
+fn split_steps(input: &str) -> Vec<String> {
+    input
+        .replace('\n', "")
+        .split(',')
+        .filter(|x| !x.is_empty())
+        .map(String::from)
         .collect()
 }
 
-fn box_index(input: &str) -> &str {
-    let x: Vec<&str> = input.split(|x| x == '=' || x == '-').collect();
-
-    x.first().unwrap()
-}
-
 fn hash_from(input: &str) -> u64 {
     input
         .chars()
@@ -108,3 +186,86 @@ fn should_calculate_sum_of_seqence() {
 
     assert_eq!(hash, 1320);
 }
+
+#[test]
+fn should_parse_steps() {
+    assert_eq!(
+        parse_steps("rn=1,cm-"),
+        Ok(vec![
+            Step::Insert {
+                label: String::from("rn"),
+                focal: 1
+            },
+            Step::Remove {
+                label: String::from("cm")
+            },
+        ])
+    );
+    assert_eq!(
+        "rn".parse::<Step>(),
+        Err(StepError::MissingOperation(String::from("rn")))
+    );
+    assert_eq!(
+        "rn=x".parse::<Step>(),
+        Err(StepError::InvalidFocal(String::from("rn=x")))
+    );
+}
+
+#[test]
+fn should_calculate_focusing_power() {
+    let input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    let power = focusing_power(input);
+
+    assert_eq!(power, Ok(145));
+}
+
+#[test]
+fn should_ignore_empty_steps() {
+    let input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7,\n";
+
+    assert_eq!(focusing_power(input), Ok(145));
+    assert_eq!(focusing_power(""), Ok(0));
+    assert_eq!(parse_steps("rn=1,,cm-").map(|x| x.len()), Ok(2));
+    assert_eq!(trace("rn=1,").map(|x| x.len()), Ok(1));
+    assert_eq!(
+        focusing_power("rn=1,cm"),
+        Err(StepError::MissingOperation(String::from("cm")))
+    );
+}
+
+#[test]
+fn should_dump_state_after_each_step() {
+    let input = "rn=1,cm-,qp=3,cm=2,qp-,pc=4,ot=9,ab=5,pc-,pc=6,ot=7";
+
+    let states = trace(input).unwrap();
+
+    assert_eq!(states[0], "After \"rn=1\":\nBox 0: [rn 1]\n");
+    assert_eq!(
+        states[10],
+        "After \"ot=7\":\nBox 0: [rn 1] [cm 2]\nBox 3: [ot 7] [ab 5] [pc 6]\n"
+    );
+}
+
+#[test]
+fn should_keep_order_when_removing_lenses() {
+    let mut boxes = LensBoxes::new();
+
+    for label in ["rn", "cm", "aju", "akd"] {
+        boxes.insert(label, 1);
+    }
+    boxes.insert("cm", 2);
+
+    assert_eq!(boxes.remove("rn"), Some(1));
+    assert_eq!(boxes.remove("rn"), None);
+    boxes.remove("aju");
+    boxes.remove("akd");
+    boxes.insert("rn", 3);
+    boxes.insert("cm", 5);
+
+    assert_eq!(
+        boxes.lenses(0).collect::<Vec<_>>(),
+        vec![("cm", 5), ("rn", 3)]
+    );
+    assert_eq!(boxes.get("cm"), Some(5));
+}
@@ -9,6 +9,6 @@ fn main() {
     println!("Solution is: {}", hash_sum);
 
     println!("Trying to solve part 2...");
-    let focusing_power = day_15::focusing_power(input.as_str());
+    let focusing_power = day_15::focusing_power(input.as_str()).unwrap();
     println!("Solution is: {}", focusing_power);
 }
@@ -1,109 +1,441 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-pub enum Dir {
-    Right,
-    Left,
-    Down,
-    Up,
+pub use grid::Dir;
+use grid::{
+    image::{Image, Rgb},
+    Grid, Pos,
+};
+
+type Tile = Pos;
+type Beam = (Tile, Dir);
+
+#[derive(Debug)]
+pub struct EntryEnergy {
+    pub start: Beam,
+    pub energized: HashSet<Tile>,
 }
 
-pub fn tiles_2(input: Vec<&str>) -> u64 {
-    let mut best = 0;
+#[derive(Debug)]
+pub struct BeamAnalysis {
+    pub entries: Vec<EntryEnergy>,
+    pub cycles: Vec<Vec<Tile>>,
+}
+
+struct Segment {
+    tiles: Vec<Tile>,
+    splitter: Option<Tile>,
+}
+
+struct SplitterGraph {
+    ids: HashMap<Tile, usize>,
+    positions: Vec<Tile>,
+    tiles: Vec<Vec<Tile>>,
+    next: Vec<Vec<usize>>,
+}
+
+struct Tarjan<'a> {
+    next: &'a [Vec<usize>],
+    index: Vec<Option<usize>>,
+    low: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    counter: usize,
+    components: Vec<Vec<usize>>,
+}
+
+pub fn tiles_2_shared(input: Vec<&str>) -> u64 {
+    analyze_beams(input)
+        .entries
+        .iter()
+        .map(|entry| entry.energized.len() as u64)
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn analyze_beams(input: Vec<&str>) -> BeamAnalysis {
+    let input = Grid::parse(&input);
+    let starts = edge_starts(&input);
+    let entry_segments = starts
+        .iter()
+        .map(|start| trace(&input, *start))
+        .collect::<Vec<_>>();
 
-    for r in 0..input.len() {
-        let to_right = tiles(input.clone(), ((r as i32, 0), Dir::Right));
+    let graph = build_splitter_graph(&input, &entry_segments);
+    let components = Tarjan::components(&graph.next);
 
-        if to_right > best {
-            best = to_right;
+    let mut component_of = vec![0; graph.positions.len()];
+    for (i, component) in components.iter().enumerate() {
+        for node in component {
+            component_of[*node] = i;
         }
+    }
 
-        let to_left = tiles(
-            input.clone(),
-            ((r as i32, input[0].len() as i32 - 1), Dir::Left),
-        );
+    let width = input.cols();
+    let words = (input.rows() * width).div_ceil(64);
+
+    // Tarjan emits components in reverse topological order, so every
+    // successor component is already resolved when we reach its predecessors.
+    let mut reachable: Vec<Vec<u64>> = Vec::with_capacity(components.len());
+    for (i, component) in components.iter().enumerate() {
+        let mut bits = vec![0; words];
+
+        for node in component {
+            for tile in &graph.tiles[*node] {
+                set_bit(&mut bits, width, *tile);
+            }
 
-        if to_left > best {
-            best = to_left;
+            for next in &graph.next[*node] {
+                if component_of[*next] != i {
+                    union_bits(&mut bits, &reachable[component_of[*next]]);
+                }
+            }
         }
+
+        reachable.push(bits);
     }
 
-    for c in 0..input[0].len() {
-        let to_down = tiles(input.clone(), ((0, c as i32), Dir::Down));
+    let entries = starts
+        .into_iter()
+        .zip(entry_segments)
+        .map(|(start, segment)| {
+            let mut bits = vec![0; words];
+
+            for tile in &segment.tiles {
+                set_bit(&mut bits, width, *tile);
+            }
+
+            if let Some(splitter) = segment.splitter {
+                union_bits(&mut bits, &reachable[component_of[graph.ids[&splitter]]]);
+            }
+
+            EntryEnergy {
+                start,
+                energized: tiles_from_bits(&bits, width),
+            }
+        })
+        .collect();
+
+    let mut cycles = components
+        .iter()
+        .filter(|component| component.len() > 1 || graph.next[component[0]].contains(&component[0]))
+        .map(|component| {
+            let mut splitters = component
+                .iter()
+                .map(|node| graph.positions[*node])
+                .collect::<Vec<_>>();
+            splitters.sort();
+            splitters
+        })
+        .collect::<Vec<_>>();
+    cycles.sort();
+
+    BeamAnalysis { entries, cycles }
+}
+
+fn edge_starts(input: &Grid<u8>) -> Vec<Beam> {
+    let rows = input.rows();
+    let cols = input.cols();
+
+    let horizontal = (0..rows).flat_map(|r| [((r, 0), Dir::East), ((r, cols - 1), Dir::West)]);
+    let vertical = (0..cols).flat_map(|c| [((0, c), Dir::South), ((rows - 1, c), Dir::North)]);
 
-        if to_down > best {
-            best = to_down;
+    horizontal.chain(vertical).collect()
+}
+
+fn build_splitter_graph(input: &Grid<u8>, entry_segments: &[Segment]) -> SplitterGraph {
+    let mut ids = HashMap::new();
+    let mut positions = vec![];
+    let mut tiles = vec![];
+    let mut hits: Vec<Vec<Tile>> = vec![];
+
+    let mut queue = entry_segments
+        .iter()
+        .filter_map(|segment| segment.splitter)
+        .collect::<Vec<_>>();
+
+    while let Some(splitter) = queue.pop() {
+        if ids.contains_key(&splitter) {
+            continue;
         }
 
-        let to_up = tiles(input.clone(), ((input.len() as i32 - 1, c as i32), Dir::Up));
+        let splitting_dir = match input[splitter] {
+            b'|' => Dir::East,
+            _ => Dir::South,
+        };
 
-        if to_up > best {
-            best = to_up;
+        let mut covered = vec![splitter];
+        let mut splitter_hits = vec![];
+
+        for beam in next_pos(input, (splitter, splitting_dir)) {
+            let segment = trace(input, beam);
+            covered.extend(segment.tiles);
+
+            if let Some(hit) = segment.splitter {
+                splitter_hits.push(hit);
+                queue.push(hit);
+            }
         }
+
+        ids.insert(splitter, positions.len());
+        positions.push(splitter);
+        tiles.push(covered);
+        hits.push(splitter_hits);
     }
 
-    best
+    let next = hits
+        .iter()
+        .map(|splitter_hits| splitter_hits.iter().map(|hit| ids[hit]).collect())
+        .collect();
+
+    SplitterGraph {
+        ids,
+        positions,
+        tiles,
+        next,
+    }
 }
 
-pub fn tiles(input: Vec<&str>, start: ((i32, i32), Dir)) -> u64 {
-    let mut pos: Vec<((i32, i32), Dir)> = vec![start];
-    let mut visited: HashSet<((i32, i32), Dir)> = HashSet::new();
-    let mut energized: HashSet<(i32, i32)> = HashSet::from_iter(vec![start.0]);
+fn trace(input: &Grid<u8>, start: Beam) -> Segment {
+    let mut tiles = vec![];
+    let mut seen = HashSet::new();
+    let mut beam = start;
 
-    while !pos.is_empty() {
-        let ((r, c), dir) = pos.remove(0);
+    while seen.insert(beam) {
+        let (pos, dir) = beam;
+        tiles.push(pos);
+
+        match turns(dir, input[pos]).as_slice() {
+            [single] => match input.step(pos, *single) {
+                Some(next) => beam = (next, *single),
+                None => break,
+            },
+            [_, _] => {
+                return Segment {
+                    tiles,
+                    splitter: Some(pos),
+                }
+            }
+            _ => break,
+        }
+    }
 
-        let curr = input
-            .get(r as usize)
-            .and_then(|row| row.chars().nth(c as usize));
+    Segment {
+        tiles,
+        splitter: None,
+    }
+}
+
+fn set_bit(bits: &mut [u64], width: usize, (r, c): Tile) {
+    let i = r * width + c;
+    bits[i / 64] |= 1 << (i % 64);
+}
+
+fn union_bits(bits: &mut [u64], other: &[u64]) {
+    for (a, b) in bits.iter_mut().zip(other) {
+        *a |= b;
+    }
+}
 
-        if curr.is_none() {
-            break;
+fn tiles_from_bits(bits: &[u64], width: usize) -> HashSet<Tile> {
+    bits.iter()
+        .enumerate()
+        .flat_map(|(word, value)| {
+            (0..64)
+                .filter(move |bit| value & (1 << bit) != 0)
+                .map(move |bit| word * 64 + bit)
+        })
+        .map(|i| (i / width, i % width))
+        .collect()
+}
+
+impl<'a> Tarjan<'a> {
+    fn components(next: &'a [Vec<usize>]) -> Vec<Vec<usize>> {
+        let mut tarjan = Tarjan {
+            next,
+            index: vec![None; next.len()],
+            low: vec![0; next.len()],
+            on_stack: vec![false; next.len()],
+            stack: vec![],
+            counter: 0,
+            components: vec![],
+        };
+
+        for node in 0..next.len() {
+            if tarjan.index[node].is_none() {
+                tarjan.visit(node);
+            }
         }
 
-        let next = next_pos(((r, c), dir), curr.unwrap())
+        tarjan.components
+    }
+
+    // Walks depth first with an explicit stack of (node, next edge) frames, so
+    // long splitter chains cannot overflow the call stack.
+    fn visit(&mut self, root: usize) {
+        let mut frames = vec![(root, 0)];
+        self.open(root);
+
+        while let Some((node, edge)) = frames.last_mut() {
+            let node = *node;
+
+            if let Some(&next) = self.next[node].get(*edge) {
+                *edge += 1;
+
+                match self.index[next] {
+                    None => {
+                        self.open(next);
+                        frames.push((next, 0));
+                    }
+                    Some(index) if self.on_stack[next] => {
+                        self.low[node] = self.low[node].min(index);
+                    }
+                    _ => {}
+                }
+
+                continue;
+            }
+
+            frames.pop();
+            self.close(node);
+
+            if let Some((parent, _)) = frames.last() {
+                self.low[*parent] = self.low[*parent].min(self.low[node]);
+            }
+        }
+    }
+
+    fn open(&mut self, node: usize) {
+        self.index[node] = Some(self.counter);
+        self.low[node] = self.counter;
+        self.counter += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+    }
+
+    fn close(&mut self, node: usize) {
+        if Some(self.low[node]) == self.index[node] {
+            let mut component = vec![];
+
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+
+                if member == node {
+                    break;
+                }
+            }
+
+            self.components.push(component);
+        }
+    }
+}
+
+pub fn tiles_2(input: Vec<&str>) -> u64 {
+    let grid = Grid::parse(&input);
+
+    edge_starts(&grid)
+        .into_iter()
+        .map(|start| energized(&grid, start).len() as u64)
+        .max()
+        .unwrap_or(0)
+}
+
+pub fn tiles(input: Vec<&str>, start: Beam) -> u64 {
+    energized(&Grid::parse(&input), start).len() as u64
+}
+
+pub fn render_energized(input: Vec<&str>, start: Beam) -> Image {
+    let input = Grid::parse(&input);
+
+    Image::render(&input, palette).overlay(energized(&input, start), [255, 220, 0])
+}
+
+fn palette(tile: &u8) -> Rgb {
+    match tile {
+        b'.' => [16, 16, 48],
+        _ => [160, 160, 200],
+    }
+}
+
+fn energized(input: &Grid<u8>, start: Beam) -> HashSet<Tile> {
+    let mut pos: Vec<Beam> = vec![start];
+    let mut visited: HashSet<Beam> = HashSet::new();
+    let mut energized: HashSet<Tile> = HashSet::from_iter(vec![start.0]);
+
+    while !pos.is_empty() {
+        let beam = pos.remove(0);
+
+        let next = next_pos(input, beam)
             .into_iter()
-            .filter(|((r, c), _)| in_bounds(input.clone(), *r, *c))
-            .filter(|((r, c), dir)| !visited.contains(&((*r, *c), *dir)))
+            .filter(|beam| !visited.contains(beam))
             .collect::<Vec<_>>();
 
         visited.extend(next.clone());
-        energized.extend(next.iter().map(|((r, c), _)| (*r, *c)));
+        energized.extend(next.iter().map(|(tile, _)| *tile));
 
         pos = [next, pos].concat();
     }
 
-    energized.len() as u64
-}
-
-fn in_bounds(input: Vec<&str>, r: i32, c: i32) -> bool {
-    r >= 0 && c >= 0 && r < input.len() as i32 && c < input[0].len() as i32
-}
-
-fn next_pos(dir: ((i32, i32), Dir), sign: char) -> Vec<((i32, i32), Dir)> {
-    let ((r, c), dir) = dir;
-
-    match (dir, sign) {
-        (Dir::Up, '/') => vec![((r, c + 1), Dir::Right)],
-        (Dir::Down, '/') => vec![((r, c - 1), Dir::Left)],
-        (Dir::Right, '/') => vec![((r - 1, c), Dir::Up)],
-        (Dir::Left, '/') => vec![((r + 1, c), Dir::Down)],
-        (Dir::Up, '\\') => vec![((r, c - 1), Dir::Left)],
-        (Dir::Down, '\\') => vec![((r, c + 1), Dir::Right)],
-        (Dir::Right, '\\') => vec![((r + 1, c), Dir::Down)],
-        (Dir::Left, '\\') => vec![((r - 1, c), Dir::Up)],
-        (Dir::Up, '|') => vec![((r - 1, c), dir)],
-        (Dir::Down, '|') => vec![((r + 1, c), dir)],
-        (Dir::Right, '|') => vec![((r + 1, c), Dir::Down), ((r - 1, c), Dir::Up)],
-        (Dir::Left, '|') => vec![((r + 1, c), Dir::Down), ((r - 1, c), Dir::Up)],
-        (Dir::Up, '-') => vec![((r, c - 1), Dir::Left), ((r, c + 1), Dir::Right)],
-        (Dir::Down, '-') => vec![((r, c - 1), Dir::Left), ((r, c + 1), Dir::Right)],
-        (Dir::Right, '-') => vec![((r, c + 1), dir)],
-        (Dir::Left, '-') => vec![((r, c - 1), dir)],
-        (Dir::Up, '.') => vec![((r - 1, c), dir)],
-        (Dir::Down, '.') => vec![((r + 1, c), dir)],
-        (Dir::Right, '.') => vec![((r, c + 1), dir)],
-        (Dir::Left, '.') => vec![((r, c - 1), dir)],
+    energized
+}
+
+fn next_pos(input: &Grid<u8>, (pos, dir): Beam) -> Vec<Beam> {
+    turns(dir, input[pos])
+        .into_iter()
+        .filter_map(|next| input.step(pos, next).map(|tile| (tile, next)))
+        .collect()
+}
+
+fn turns(dir: Dir, sign: u8) -> Vec<Dir> {
+    match (sign, dir) {
+        (b'/', Dir::North | Dir::South) => vec![dir.turn_right()],
+        (b'/', _) => vec![dir.turn_left()],
+        (b'\\', Dir::North | Dir::South) => vec![dir.turn_left()],
+        (b'\\', _) => vec![dir.turn_right()],
+        (b'|', Dir::East | Dir::West) => vec![Dir::South, Dir::North],
+        (b'-', Dir::North | Dir::South) => vec![Dir::West, Dir::East],
+        (b'|' | b'-' | b'.', _) => vec![dir],
         _ => vec![],
     }
 }
+
+#[test]
+fn should_energize_tiles_for_every_entry_with_shared_work() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    let analysis = analyze_beams(input.clone());
+
+    assert_eq!(analysis.entries.len(), 2 * (input.len() + input[0].len()));
+    for entry in &analysis.entries {
+        assert_eq!(
+            entry.energized.len() as u64,
+            tiles(input.clone(), entry.start)
+        );
+    }
+    assert_eq!(tiles_2(input.clone()), 51);
+    assert_eq!(tiles_2_shared(input), 51);
+}
+
+#[test]
+fn should_report_splitters_forming_cycles() {
+    let input = vec!["......", ".|..\\.", "......", ".\\..|.", "......"];
+
+    let analysis = analyze_beams(input);
+
+    assert_eq!(analysis.cycles, vec![vec![(1, 1), (3, 4)]]);
+}
+
+#[test]
+fn should_render_energized_tiles() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    let image = render_energized(input.clone(), ((0, 0), Dir::East));
+    let map = Image::render(&Grid::parse(&input), palette);
+
+    let lit = (0..image.height())
+        .flat_map(|r| (0..image.width()).map(move |c| (r, c)))
+        .filter(|pos| image.pixel(*pos) != map.pixel(*pos))
+        .count();
+
+    assert_eq!(lit, 46);
+}
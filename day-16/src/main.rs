@@ -6,14 +6,14 @@ fn main() {
 
     println!("Trying to solve part 1...");
     let start = std::time::Instant::now();
-    let tiles = day_16::tiles(input.lines().collect(), ((0, 0), day_16::Dir::Right));
+    let tiles = day_16::tiles(input.lines().collect(), ((0, 0), day_16::Dir::East));
     let elapsed = start.elapsed();
     println!("Solution is: {}", tiles);
     println!("Elapsed time: {:?}", elapsed);
 
     println!("Trying to solve part 2...");
     let start = std::time::Instant::now();
-    let tiles = day_16::tiles_2(input.lines().collect());
+    let tiles = day_16::tiles_2_shared(input.lines().collect());
     let elapsed = start.elapsed();
     println!("Solution is: {}", tiles);
     println!("Elapsed time: {:?}", elapsed);
@@ -3,16 +3,12 @@ use std::{
     collections::{BinaryHeap, HashMap},
 };
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-enum Dir {
-    North,
-    South,
-    West,
-    East,
-}
+use grid::{
+    image::{Image, Rgb},
+    Dir, Grid, Pos,
+};
 
-type Coordinate = (usize, usize);
-type Position = (Coordinate, Dir);
+type Position = (grid::Pos, Dir);
 
 #[derive(Copy, Clone, Eq, PartialEq, Debug)]
 struct State {
@@ -54,15 +50,33 @@ impl PartialOrd for State {
 }
 
 pub fn heat_loss_ultra(input: Vec<&str>) -> usize {
-    dijkstra(&input, 4, 10)
+    dijkstra(&heat_map(&input), 4, 10).0
 }
 
 pub fn heat_loss(input: Vec<&str>) -> usize {
-    dijkstra(&input, 1, 3)
+    dijkstra(&heat_map(&input), 1, 3).0
+}
+
+pub fn render_path(input: Vec<&str>, min_steps: u8, max_steps: u8) -> Image {
+    let heat = heat_map(&input);
+    let (_, path) = dijkstra(&heat, min_steps, max_steps);
+
+    Image::render(&heat, palette).overlay(path, [0, 160, 255])
+}
+
+fn palette(heat: &usize) -> Rgb {
+    let level = (*heat * 255 / 9) as u8;
+
+    [level, level / 3, 0]
+}
+
+fn heat_map(input: &[&str]) -> Grid<usize> {
+    Grid::parse(input).map(|x| (x - b'0') as usize)
 }
 
-fn dijkstra(input: &Vec<&str>, min_steps: u8, max_steps: u8) -> usize {
-    let target = ((input.len() - 1), (input[0].len() - 1));
+// Returns the lowest heat loss together with the crucible's path.
+fn dijkstra(input: &Grid<usize>, min_steps: u8, max_steps: u8) -> (usize, Vec<Pos>) {
+    let target = ((input.rows() - 1), (input.cols() - 1));
 
     let east_start = State {
         cost: 0,
@@ -84,6 +98,8 @@ fn dijkstra(input: &Vec<&str>, min_steps: u8, max_steps: u8) -> usize {
     distances.insert(east_start.into(), 0);
     distances.insert(south_start.into(), 0);
 
+    let mut previous: HashMap<StateKey, StateKey> = HashMap::new();
+
     while let Some(
         state @ State {
             cost,
@@ -93,7 +109,16 @@ fn dijkstra(input: &Vec<&str>, min_steps: u8, max_steps: u8) -> usize {
     ) = priority_queue.pop()
     {
         if position.0 == target && steps >= min_steps {
-            return cost;
+            let mut key = StateKey::from(state);
+            let mut path = vec![key.position.0];
+
+            while let Some(prev) = previous.get(&key) {
+                path.push(prev.position.0);
+                key = *prev;
+            }
+            path.reverse();
+
+            return (cost, path);
         }
 
         if distances.contains_key(&state.into()) && distances.get(&state.into()).unwrap() < &cost {
@@ -101,8 +126,7 @@ fn dijkstra(input: &Vec<&str>, min_steps: u8, max_steps: u8) -> usize {
         }
 
         for next_position in next_positions(input, &position) {
-            let heat = heat(input, &next_position.0);
-            let new_heat = cost + heat as usize;
+            let new_heat = cost + input[next_position.0];
 
             let curr_dir = position.1;
             let next_dir = next_position.1;
@@ -125,44 +149,31 @@ fn dijkstra(input: &Vec<&str>, min_steps: u8, max_steps: u8) -> usize {
             }
 
             distances.insert(next_state.into(), new_heat);
+            previous.insert(next_state.into(), state.into());
             priority_queue.push(next_state);
         }
     }
 
-    0
+    (0, vec![])
 }
 
-fn next_positions(input: &Vec<&str>, pos: &Position) -> Vec<Position> {
-    let ((r, c), dir) = pos;
-
-    vec![
-        ((r.saturating_sub(1), *c), Dir::North),
-        ((r + 1, *c), Dir::South),
-        ((*r, c.saturating_sub(1)), Dir::West),
-        ((*r, c + 1), Dir::East),
-    ]
-    .into_iter()
-    .filter(|(x, d)| *d != opposite_dir(dir) && *x != pos.0 && in_bound(input, *x))
-    .collect()
-}
+fn next_positions(input: &Grid<usize>, pos: &Position) -> Vec<Position> {
+    let (coord, dir) = pos;
 
-fn opposite_dir(dir: &Dir) -> Dir {
-    match dir {
-        Dir::North => Dir::South,
-        Dir::South => Dir::North,
-        Dir::West => Dir::East,
-        Dir::East => Dir::West,
-    }
+    input
+        .neighbours(*coord)
+        .filter(|(_, d)| *d != dir.opposite())
+        .collect()
 }
 
-fn heat(input: &Vec<&str>, (r, c): &Coordinate) -> u32 {
-    let heat = input.get(*r).unwrap().chars().nth(*c).unwrap();
-
-    heat.to_digit(10).unwrap()
-}
+#[test]
+fn should_return_path_with_lowest_heat_loss() {
+    let heat = heat_map(&include_str!("sample.txt").lines().collect::<Vec<_>>());
 
-fn in_bound(input: &Vec<&str>, coord: Coordinate) -> bool {
-    let (r, c) = coord;
+    let (cost, path) = dijkstra(&heat, 1, 3);
 
-    r < input.len() && c < input[0].len()
+    assert_eq!(cost, 102);
+    assert_eq!(path.first(), Some(&(0, 0)));
+    assert_eq!(path.last(), Some(&(heat.rows() - 1, heat.cols() - 1)));
+    assert_eq!(path[1..].iter().map(|pos| heat[*pos]).sum::<usize>(), 102);
 }
@@ -0,0 +1,242 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum ParseError {
+    MissingSeparator(String),
+    InvalidId(String),
+    InvalidCube(String),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Bag {
+    cubes: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Game {
+    pub id: u32,
+    pub draws: Vec<Bag>,
+}
+
+/// Parses one game per line.
+///
+/// # Errors
+///
+/// Returns the [`ParseError`] of the first line that is not a valid game.
+pub fn parse_games(input: &[&str]) -> Result<Vec<Game>, ParseError> {
+    input.iter().map(|line| line.parse()).collect()
+}
+
+/// For every colour, lists the counts at which further games become possible
+/// when only that colour is changed and all other counts stay as in `bag`.
+/// Games that need more of another colour never show up.
+#[must_use]
+pub fn possible_games_by_count(
+    games: &[Game],
+    bag: &Bag,
+) -> BTreeMap<String, Vec<(u32, Vec<u32>)>> {
+    let colors = games
+        .iter()
+        .flat_map(|game| game.draws.iter().flat_map(Bag::colors))
+        .chain(bag.colors())
+        .map(str::to_string)
+        .collect::<BTreeSet<String>>();
+
+    colors
+        .into_iter()
+        .map(|color| {
+            let mut thresholds = BTreeMap::<u32, Vec<u32>>::new();
+
+            for game in games {
+                let mut needed = Bag::minimal_bag(game);
+                let count = needed.get(&color);
+                needed.set(&color, 0);
+
+                if bag.contains(&needed) {
+                    thresholds.entry(count).or_default().push(game.id);
+                }
+            }
+
+            (color, thresholds.into_iter().collect())
+        })
+        .collect()
+}
+
+impl Bag {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with(mut self, color: &str, count: u32) -> Self {
+        self.set(color, count);
+        self
+    }
+
+    #[must_use]
+    pub fn get(&self, color: &str) -> u32 {
+        self.cubes.get(color).copied().unwrap_or(0)
+    }
+
+    pub fn set(&mut self, color: &str, count: u32) {
+        self.cubes.insert(color.to_string(), count);
+    }
+
+    pub fn colors(&self) -> impl Iterator<Item = &str> {
+        self.cubes.keys().map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn contains(&self, other: &Bag) -> bool {
+        other
+            .cubes
+            .iter()
+            .all(|(color, count)| self.get(color) >= *count)
+    }
+
+    #[must_use]
+    pub fn is_possible(&self, game: &Game) -> bool {
+        game.draws.iter().all(|draw| self.contains(draw))
+    }
+
+    #[must_use]
+    pub fn minimal_bag(game: &Game) -> Bag {
+        game.draws.iter().fold(Bag::new(), |mut bag, draw| {
+            for (color, count) in &draw.cubes {
+                if *count > bag.get(color) {
+                    bag.set(color, *count);
+                }
+            }
+            bag
+        })
+    }
+
+    #[must_use]
+    pub fn power(&self) -> u64 {
+        self.cubes.values().map(|count| u64::from(*count)).product()
+    }
+}
+
+impl<'a> FromIterator<(&'a str, u32)> for Bag {
+    fn from_iter<T: IntoIterator<Item = (&'a str, u32)>>(iter: T) -> Self {
+        iter.into_iter()
+            .fold(Bag::new(), |bag, (color, count)| bag.with(color, count))
+    }
+}
+
+impl FromStr for Bag {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        input
+            .split(", ")
+            .map(|cube| match cube.trim().split_once(' ') {
+                Some((count, color)) if !color.is_empty() => count
+                    .parse::<u32>()
+                    .map(|count| (color, count))
+                    .map_err(|_| ParseError::InvalidCube(cube.to_string())),
+                _ => Err(ParseError::InvalidCube(cube.to_string())),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Game {
+    type Err = ParseError;
+
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let (head, draws) = input
+            .split_once(": ")
+            .ok_or_else(|| ParseError::MissingSeparator(input.to_string()))?;
+
+        let id = head
+            .strip_prefix("Game ")
+            .and_then(|id| id.parse::<u32>().ok())
+            .ok_or_else(|| ParseError::InvalidId(head.to_string()))?;
+
+        let draws = draws
+            .split("; ")
+            .map(str::parse)
+            .collect::<Result<Vec<Bag>, ParseError>>()?;
+
+        Ok(Self { id, draws })
+    }
+}
+
+#[test]
+fn should_parse_game_with_arbitrary_colors() {
+    let game = "Game 7: 2 purple, 1 red; 3 teal".parse::<Game>();
+
+    assert_eq!(
+        game,
+        Ok(Game {
+            id: 7,
+            draws: vec![
+                Bag::from_iter([("purple", 2), ("red", 1)]),
+                Bag::from_iter([("teal", 3)]),
+            ],
+        })
+    );
+}
+
+#[test]
+fn should_report_parse_errors() {
+    assert_eq!(
+        "Game 1 3 blue".parse::<Game>(),
+        Err(ParseError::MissingSeparator("Game 1 3 blue".to_string()))
+    );
+    assert_eq!(
+        "Round 1: 3 blue".parse::<Game>(),
+        Err(ParseError::InvalidId("Round 1".to_string()))
+    );
+    assert_eq!(
+        "Game 1: 3 blue, red".parse::<Game>(),
+        Err(ParseError::InvalidCube("red".to_string()))
+    );
+}
+
+#[test]
+fn should_check_possibility_and_power_with_bag() {
+    let game = "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green"
+        .parse::<Game>()
+        .unwrap();
+
+    let bag = Bag::from_iter([("red", 12), ("green", 13), ("blue", 14)]);
+    let minimal = Bag::minimal_bag(&game);
+
+    assert!(bag.is_possible(&game));
+    assert!(!bag.clone().with("blue", 5).is_possible(&game));
+    assert_eq!(
+        minimal,
+        Bag::from_iter([("red", 4), ("green", 2), ("blue", 6)])
+    );
+    assert_eq!(minimal.power(), 48);
+}
+
+#[test]
+fn should_list_games_becoming_possible_per_color_count() {
+    let games = parse_games(&[
+        "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+        "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+        "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+        "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+    ])
+    .unwrap();
+
+    let bag = Bag::from_iter([("red", 12), ("green", 13), ("blue", 14)]);
+    let thresholds = possible_games_by_count(&games, &bag);
+
+    assert_eq!(
+        thresholds["red"],
+        vec![(1, vec![2]), (4, vec![1]), (6, vec![5]), (20, vec![3])]
+    );
+    assert_eq!(
+        thresholds["blue"],
+        vec![(2, vec![5]), (4, vec![2]), (6, vec![1])]
+    );
+}
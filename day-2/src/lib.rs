@@ -2,6 +2,8 @@
 
 use std::collections::HashMap;
 
+pub mod cubes;
+
 #[derive(Debug, PartialEq)]
 struct Game {
     id: u32,
@@ -34,3 +34,29 @@ fn should_calculate_sum_of_power_of_cube_sets() {
 
     assert_eq!(score, 2286);
 }
+
+#[test]
+fn should_calculate_score_and_power_with_typed_games() {
+    let input = [
+        "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green",
+        "Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue",
+        "Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red",
+        "Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red",
+        "Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+    ];
+
+    let games = day_2::cubes::parse_games(&input).unwrap();
+    let bag = day_2::cubes::Bag::from_iter([("red", 12), ("green", 13), ("blue", 14)]);
+
+    let score = games
+        .iter()
+        .filter(|game| bag.is_possible(game))
+        .map(|game| game.id)
+        .sum::<u32>();
+    let power = games
+        .iter()
+        .map(|game| day_2::cubes::Bag::minimal_bag(game).power())
+        .sum::<u64>();
+
+    assert_eq!((score, power), (8, 2286));
+}
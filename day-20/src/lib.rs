@@ -1,5 +1,6 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
 
+use cycle_detection::Cycle;
 use num_integer::lcm;
 
 type Name = String;
@@ -8,7 +9,7 @@ type Network = HashMap<Name, Box<dyn Module>>;
 type Next = HashMap<Name, Destinations>;
 type Memory = HashMap<Name, Pulse>;
 
-#[derive(Debug, Clone, Default, Eq, PartialEq)]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Hash)]
 enum Pulse {
     #[default]
     Low,
@@ -30,6 +31,10 @@ trait Module {
     fn next(&self, _: Pulse) -> bool {
         true
     }
+    fn snapshot(&self) -> Vec<Pulse> {
+        vec![]
+    }
+    fn reset(&mut self);
 }
 
 #[derive(Default, Clone, Debug)]
@@ -48,6 +53,14 @@ impl Module for FlipFlop {
     fn next(&self, pulse: Pulse) -> bool {
         pulse != Pulse::High
     }
+
+    fn snapshot(&self) -> Vec<Pulse> {
+        vec![self.state.clone()]
+    }
+
+    fn reset(&mut self) {
+        self.state = Pulse::Low;
+    }
 }
 
 #[derive(Default, Clone, Debug)]
@@ -60,6 +73,10 @@ impl Module for Broadcaster {
         self.state = signal;
         self.state.clone()
     }
+
+    fn reset(&mut self) {
+        self.state = Pulse::Low;
+    }
 }
 
 #[derive(Default, Clone, Debug)]
@@ -83,6 +100,18 @@ impl Module for Conjunction {
     fn memory(&mut self, name: Name) {
         self.inputs.insert(name, Pulse::Low);
     }
+
+    fn snapshot(&self) -> Vec<Pulse> {
+        let mut inputs = self.inputs.iter().collect::<Vec<_>>();
+        inputs.sort_by_key(|(name, _)| *name);
+
+        inputs.into_iter().map(|(_, pulse)| pulse.clone()).collect()
+    }
+
+    fn reset(&mut self) {
+        self.inputs.values_mut().for_each(|x| *x = Pulse::Low);
+        self.state = Pulse::Low;
+    }
 }
 
 fn build_conjunctions(network: &mut Network, next: &Next) {
@@ -102,55 +131,103 @@ fn value_as_output(input: String, next: &Next) -> Vec<String> {
         .collect()
 }
 
-// Solved part two with the help of:
-// https://www.youtube.com/watch?v=lxm6i21O83k
-pub fn reach_rx_low(vec: Vec<&str>) -> usize {
-    let (mut network, next) = parse(vec);
+fn upstream(output: &str, next: &Next) -> Vec<Name> {
+    let mut seen = HashSet::from([output.to_string()]);
+    let mut queue = vec![output.to_string()];
 
-    let feed = value_as_output("rx".to_string(), &next)[0].clone();
-    let cycles = value_as_output(feed.to_string(), &next);
+    while let Some(name) = queue.pop() {
+        for input in value_as_output(name, next) {
+            if seen.insert(input.clone()) {
+                queue.push(input);
+            }
+        }
+    }
 
-    let mut cycle_length: HashMap<String, usize> = HashMap::new();
-    let mut seen = cycles
-        .iter()
-        .map(|v| (v.to_string(), 0))
-        .collect::<HashMap<String, usize>>();
+    let mut cone = seen.into_iter().collect::<Vec<_>>();
+    cone.sort();
+    cone
+}
 
-    build_conjunctions(&mut network, &next);
+// Returns whether `watch` sent a high pulse during the press.
+fn press(network: &mut Network, next: &Next, watch: &str) -> bool {
+    let mut sent_high = false;
+    let mut queue = VecDeque::new();
+
+    queue.push_back(("broadcaster".to_string(), Pulse::Low));
+    while let Some((name, pulse)) = queue.pop_front() {
+        sent_high |= name == watch && pulse == Pulse::High;
+
+        for n in next.get(&name).unwrap() {
+            if n == "rx" || n == "output" {
+                continue;
+            }
+
+            let module = network.get_mut(n).unwrap();
+            if !module.next(pulse.clone()) {
+                continue;
+            }
+
+            let pulse = module.tick(pulse.clone(), name.clone());
+            queue.push_back((n.clone(), pulse));
+        }
+    }
+
+    sent_high
+}
+
+// Runs the network from its initial state until the cone feeding `output`
+// repeats. The counters in the puzzle send their first high pulse on the last
+// press of their period, which is checked against the detected cycle.
+fn counter_cycle(network: &mut Network, next: &Next, output: &str) -> Cycle {
+    network.values_mut().for_each(|x| x.reset());
 
+    let cone = upstream(output, next);
+    let mut first_high = None;
     let mut presses = 0;
-    loop {
+
+    let states = std::iter::repeat_with(|| {
+        let snapshot = cone
+            .iter()
+            .flat_map(|name| network[name].snapshot())
+            .collect::<Vec<_>>();
+
         presses += 1;
-        let mut queue = VecDeque::new();
+        if press(network, next, output) && first_high.is_none() {
+            first_high = Some(presses);
+        }
 
-        queue.push_back(("broadcaster".to_string(), Pulse::Low));
-        while let Some((name, pulse)) = queue.pop_front() {
-            for n in next.get(&name).unwrap() {
-                if *n == feed && pulse == Pulse::High {
-                    let next_seen = seen.get_mut(&name).unwrap().saturating_add(1);
-                    seen.insert(name.clone(), next_seen);
+        snapshot
+    });
 
-                    cycle_length.insert(name.clone(), presses);
+    let cycle = cycle_detection::hashed(states)
+        .expect("presses never end")
+        .cycle;
 
-                    if seen.iter().all(|(_, v)| v == &1) {
-                        return cycle_length.iter().fold(1, |acc, (_, v)| lcm(acc, *v));
-                    }
-                }
+    assert_eq!(
+        Some(cycle.lambda),
+        first_high,
+        "{} should first send a high pulse after one period",
+        output
+    );
 
-                if n == "rx" || n == "output" {
-                    continue;
-                }
+    cycle
+}
 
-                let module = network.get_mut(n).unwrap();
-                if !module.next(pulse.clone()) {
-                    continue;
-                }
+// Solved part two with the help of:
+// https://www.youtube.com/watch?v=lxm6i21O83k
+//
+// Every input of the conjunction feeding rx is driven by its own counter, so
+// rx receives a low pulse once all of their periods line up.
+pub fn reach_rx_low(vec: Vec<&str>) -> usize {
+    let (mut network, next) = parse(vec);
+    build_conjunctions(&mut network, &next);
 
-                let pulse = module.tick(pulse.clone(), name.clone());
-                queue.push_back((n.clone(), pulse));
-            }
-        }
-    }
+    let feed = value_as_output("rx".to_string(), &next)[0].clone();
+
+    value_as_output(feed, &next)
+        .iter()
+        .map(|input| counter_cycle(&mut network, &next, input).lambda)
+        .fold(1, lcm)
 }
 
 pub fn pulses(vec: Vec<&str>) -> usize {
@@ -235,3 +312,28 @@ fn parse_module_type(x: &str) -> (Name, Box<dyn Module>) {
         },
     }
 }
+
+#[test]
+fn should_find_counter_periods_feeding_rx() {
+    // Two counters of period 3 (b0, b1) and 5 (d0, d1, d2).
+    let input = vec![
+        "broadcaster -> b0, d0",
+        "%b0 -> b1, c",
+        "%b1 -> c",
+        "&c -> b0, v",
+        "&v -> fd",
+        "%d0 -> d1, e",
+        "%d1 -> d2",
+        "%d2 -> e",
+        "&e -> d0, d1, w",
+        "&w -> fd",
+        "&fd -> rx",
+    ];
+
+    let (mut network, next) = parse(input.clone());
+    build_conjunctions(&mut network, &next);
+
+    assert_eq!(counter_cycle(&mut network, &next, "v").lambda, 3);
+    assert_eq!(counter_cycle(&mut network, &next, "w").lambda, 5);
+    assert_eq!(reach_rx_low(input), 15);
+}
@@ -1,50 +1,74 @@
 use std::collections::HashSet;
 
+use grid::{
+    image::{Image, Rgb},
+    Grid, Pos,
+};
+
 pub fn plots(collect: Vec<&str>, steps: usize) -> usize {
-    let mut curr_pos = HashSet::from([start(collect.clone())]);
+    reachable(&Grid::parse(&collect), steps).len()
+}
+
+pub fn render_plots(collect: Vec<&str>, steps: usize) -> Image {
+    let garden = Grid::parse(&collect);
+
+    Image::render(&garden, palette).overlay(reachable(&garden, steps), [64, 160, 255])
+}
+
+fn palette(tile: &u8) -> Rgb {
+    match tile {
+        b'#' => [40, 40, 40],
+        b'S' => [255, 200, 0],
+        _ => [96, 160, 64],
+    }
+}
+
+fn reachable(garden: &Grid<u8>, steps: usize) -> HashSet<Pos> {
+    let mut curr_pos = HashSet::from([start(garden)]);
 
     for _ in 0..steps {
-        curr_pos = next_poss(collect.clone(), curr_pos)
+        curr_pos = next_poss(garden, curr_pos)
             .iter()
-            .filter(|(r, c)| collect[*r].chars().nth(*c).unwrap() == '.')
+            .filter(|p| garden[**p] != b'#')
             .copied()
             .collect();
     }
 
-    curr_pos.len() + 1
+    curr_pos
 }
 
-type Pos = (usize, usize);
+const START: u8 = b'S';
 
-const START: char = 'S';
-
-fn start(garden: Vec<&str>) -> Pos {
-    garden
-        .iter()
-        .enumerate()
-        .find(|(_, l)| l.contains(START))
-        .map(|(r, l)| (r, l.find(START).unwrap()))
-        .unwrap()
+fn start(garden: &Grid<u8>) -> Pos {
+    garden.position(|x| *x == START).unwrap()
 }
 
-fn next_poss(garden: Vec<&str>, poss: HashSet<Pos>) -> HashSet<Pos> {
+fn next_poss(garden: &Grid<u8>, poss: HashSet<Pos>) -> HashSet<Pos> {
     poss.iter()
-        .flat_map(|p| next_pos(garden.clone(), *p))
+        .flat_map(|p| garden.neighbours(*p).map(|(x, _)| x))
         .collect::<HashSet<_>>()
 }
 
-fn next_pos(garden: Vec<&str>, (r, c): Pos) -> Vec<Pos> {
-    [(r + 1, c),
-        (r, c + 1),
-        (r.saturating_sub(1), c),
-        (r, c.saturating_sub(1))]
-    .iter()
-    .filter(|p| in_garden(&garden, **p))
-    .copied()
-    .collect()
+#[test]
+fn should_count_plots_after_odd_and_even_steps() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    assert_eq!(plots(input.clone(), 1), 2);
+    assert_eq!(plots(input.clone(), 2), 4);
+    assert_eq!(plots(input, 6), 16);
 }
 
-fn in_garden(garden: &Vec<&str>, pos: Pos) -> bool {
-    let (r, c) = pos;
-    r < garden.len() && c < garden[r].len()
+#[test]
+fn should_render_reachable_plots() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    let image = render_plots(input.clone(), 6);
+    let map = Image::render(&Grid::parse(&input), palette);
+
+    let lit = (0..image.height())
+        .flat_map(|r| (0..image.width()).map(move |c| (r, c)))
+        .filter(|pos| image.pixel(*pos) != map.pixel(*pos))
+        .count();
+
+    assert_eq!(lit, 16);
 }
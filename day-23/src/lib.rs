@@ -3,60 +3,59 @@ use std::{
     collections::{HashMap, HashSet, VecDeque},
 };
 
+use grid::{
+    image::{Image, Rgb},
+    Dir, Grid, Pos,
+};
+
 pub fn longest_path_slope(input: Vec<&str>) -> usize {
-    walk(input)
+    walk(&Grid::parse(&input)).len() - 1
 }
 
-pub fn longest_path(input: Vec<&str>) -> isize {
-    let start = (0, 1);
-    let end = (input.len() - 1, input[0].len() - 2);
+pub fn render_hike(input: Vec<&str>) -> Image {
+    let trails = Grid::parse(&input);
 
-    find_longest_path(start, end, &distances(input.clone(), points(input.clone())))
+    Image::render(&trails, palette).overlay(walk(&trails), [255, 64, 160])
 }
 
-#[derive(PartialEq, Eq, Copy, Clone, Debug, Hash)]
-enum Dir {
-    North,
-    South,
-    West,
-    East,
+fn palette(tile: &u8) -> Rgb {
+    match tile {
+        b'#' => [24, 64, 24],
+        b'.' => [200, 200, 180],
+        _ => [120, 120, 220],
+    }
 }
 
-impl Dir {
-    fn opposite(&self) -> Dir {
-        match self {
-            Dir::North => Dir::South,
-            Dir::South => Dir::North,
-            Dir::West => Dir::East,
-            Dir::East => Dir::West,
-        }
-    }
+pub fn longest_path(input: Vec<&str>) -> isize {
+    let trails = Grid::parse(&input);
+    let start = (0, 1);
+    let end = (trails.rows() - 1, trails.cols() - 2);
+
+    find_longest_path(start, end, &distances(&trails, points(&trails)))
 }
 
 type Position = ((usize, usize), Dir);
 type Distances = HashMap<(usize, usize), HashMap<(usize, usize), isize>>;
 
-fn points(trails: Vec<&str>) -> Vec<(usize, usize)> {
+fn points(trails: &Grid<u8>) -> Vec<(usize, usize)> {
     let start = (0, 1);
-    let end = (trails.len() - 1, trails[0].len() - 2);
+    let end = (trails.rows() - 1, trails.cols() - 2);
 
     let mut points = vec![start, end];
-    for (r, row) in trails.iter().enumerate() {
-        for (c, ch) in row.chars().enumerate() {
-            if ch == '#' {
-                continue;
-            }
+    for (pos, ch) in trails.iter() {
+        if *ch == b'#' {
+            continue;
+        }
 
-            if next(&trails, &(r, c)).len() >= 3 {
-                points.push((r, c));
-            }
+        if next(trails, &pos).len() >= 3 {
+            points.push(pos);
         }
     }
 
     points
 }
 
-fn distances(trails: Vec<&str>, points: Vec<(usize, usize)>) -> Distances {
+fn distances(trails: &Grid<u8>, points: Vec<(usize, usize)>) -> Distances {
     let mut distances = HashMap::<(usize, usize), HashMap<(usize, usize), isize>>::new();
 
     for (sr, sc) in &points {
@@ -69,7 +68,7 @@ fn distances(trails: Vec<&str>, points: Vec<(usize, usize)>) -> Distances {
                 continue;
             }
 
-            for (dr, dc) in next(&trails, &(nr, nc)) {
+            for (dr, dc) in next(trails, &(nr, nc)) {
                 if !seen.contains(&(dr, dc)) {
                     queue.push_front((dr, dc, w + 1));
                     seen.insert((dr, dc));
@@ -115,71 +114,91 @@ fn find_longest_path(start: (usize, usize), end: (usize, usize), distances: &Dis
     dfs(start, end, seen, distances)
 }
 
-fn walk(trails: Vec<&str>) -> usize {
+// Depth first, keeping only the current hike and the moves still left to try
+// from each of its tiles, so memory stays bounded by the length of one hike.
+fn walk(trails: &Grid<u8>) -> Vec<Pos> {
     let start = ((0, 1), Dir::South);
 
-    assert!(trails[0].chars().nth(1).unwrap() == '.');
+    assert!(trails[start.0] == b'.');
 
-    let end = (trails.len() - 1, trails[0].len() - 2);
+    let end = (trails.rows() - 1, trails.cols() - 2);
 
-    let mut queue = VecDeque::from(
-        next_with_slope(&trails, &start)
-            .iter()
-            .map(|p| (*p, 1))
-            .collect::<Vec<(Position, usize)>>(),
-    );
+    let mut hike = vec![start.0];
+    let mut moves = vec![next_with_slope(trails, &start)];
+    let mut longest = vec![];
 
-    let mut hikes = vec![];
+    while let Some(left) = moves.last_mut() {
+        let Some(pos) = left.pop() else {
+            moves.pop();
+            hike.pop();
+            continue;
+        };
+
+        hike.push(pos.0);
 
-    while let Some(pos) = queue.pop_front() {
-        if pos.0 .0 == end {
-            hikes.push(pos.1);
+        if pos.0 == end {
+            if hike.len() > longest.len() {
+                longest = hike.clone();
+            }
+            hike.pop();
             continue;
         }
 
-        let next = next_with_slope(&trails, &pos.0);
-        for n in next {
-            queue.push_back((n, pos.1 + 1));
-        }
+        moves.push(next_with_slope(trails, &pos));
     }
 
-    *hikes.iter().max().unwrap()
+    longest
 }
 
-fn next(trails: &Vec<&str>, (r, c): &(usize, usize)) -> Vec<(usize, usize)> {
-    vec![
-        ((*r, *c + 1)),
-        ((*r, c.saturating_sub(1))),
-        ((*r + 1, *c)),
-        ((r.saturating_sub(1), *c)),
-    ]
-    .into_iter()
-    .filter(|(nr, nc)| nr < &trails.len() && nc < &trails[0].len())
-    .filter(|(nr, nc)| trails[*nr].chars().nth(*nc).unwrap() != '#')
-    .collect()
+fn next(trails: &Grid<u8>, pos: &(usize, usize)) -> Vec<(usize, usize)> {
+    trails
+        .neighbours(*pos)
+        .map(|(n, _)| n)
+        .filter(|n| trails[*n] != b'#')
+        .collect()
 }
 
-fn next_with_slope(trails: &Vec<&str>, ((r, c), d): &Position) -> Vec<Position> {
-    slope(trails, &((*r, *c), *d))
+fn next_with_slope(trails: &Grid<u8>, (pos, d): &Position) -> Vec<Position> {
+    slope(trails, pos)
         .map(|p| vec![p])
-        .unwrap_or(vec![
-            ((*r, *c + 1), Dir::East),
-            ((*r, c.saturating_sub(1)), Dir::West),
-            ((*r + 1, *c), Dir::South),
-            ((r.saturating_sub(1), *c), Dir::North),
-        ])
+        .unwrap_or_else(|| trails.neighbours(*pos).collect())
         .into_iter()
         .filter(|(_, n)| *n != d.opposite())
-        .filter(|((r, c), _)| trails[*r].chars().nth(*c).unwrap() != '#')
+        .filter(|(n, _)| trails[*n] != b'#')
         .collect()
 }
 
-fn slope(trails: &Vec<&str>, ((r, c), _): &Position) -> Option<Position> {
-    match trails[*r].chars().nth(*c).unwrap() {
-        '>' => Some(((*r, *c + 1), Dir::East)),
-        '<' => Some(((*r, c.saturating_sub(1)), Dir::West)),
-        'v' => Some(((*r + 1, *c), Dir::South)),
-        '^' => Some(((r.saturating_sub(1), *c), Dir::North)),
-        _ => None,
-    }
+fn slope(trails: &Grid<u8>, pos: &(usize, usize)) -> Option<Position> {
+    let dir = match trails[*pos] {
+        b'>' => Dir::East,
+        b'<' => Dir::West,
+        b'v' => Dir::South,
+        b'^' => Dir::North,
+        _ => return None,
+    };
+
+    trails.step(*pos, dir).map(|n| (n, dir))
+}
+
+#[test]
+fn should_find_longest_hikes() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    assert_eq!(longest_path_slope(input.clone()), 94);
+    assert_eq!(longest_path(input), 154);
+}
+
+#[test]
+fn should_render_longest_hike() {
+    let input = include_str!("sample.txt").lines().collect::<Vec<_>>();
+
+    let image = render_hike(input.clone());
+    let map = Image::render(&Grid::parse(&input), palette);
+
+    let lit = (0..image.height())
+        .flat_map(|r| (0..image.width()).map(move |c| (r, c)))
+        .filter(|pos| image.pixel(*pos) != map.pixel(*pos))
+        .count();
+
+    assert_eq!(lit, 95);
 }
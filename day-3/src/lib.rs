@@ -2,6 +2,7 @@
 
 use std::collections::HashMap;
 
+use grid::Grid;
 use lazy_static::lazy_static;
 use regex::Regex;
 
@@ -53,6 +54,29 @@ struct Number {
     value: u32,
 }
 
+#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+pub struct PartNumber {
+    pub row: usize,
+    pub col_from: usize,
+    pub col_to: usize,
+    pub value: u32,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Gear {
+    pub row: usize,
+    pub col: usize,
+    pub numbers: Vec<u32>,
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineGrid {
+    cells: Grid<Option<usize>>,
+    numbers: Vec<PartNumber>,
+    symbols: HashMap<(usize, usize), char>,
+    touching: HashMap<(usize, usize), Vec<usize>>,
+}
+
 lazy_static! {
     static ref NUMBER_REGEX: Regex = Regex::new(r"\d+").unwrap();
     static ref SYMBOL_REGEX: Regex = Regex::new(r"[=#$+*%&@/-]").unwrap();
@@ -164,6 +188,135 @@ fn extract_numbers_from_line(line_number: usize, line: &str) -> Vec<Number> {
         .collect()
 }
 
+impl EngineGrid {
+    /// Every character that is neither a digit nor `.` counts as a symbol.
+    #[must_use]
+    pub fn new(lines: &[&str]) -> Self {
+        let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+        let mut cells = Grid::filled(lines.len(), width, None);
+        let mut numbers = vec![];
+        let mut symbols = HashMap::new();
+
+        for (row, line) in lines.iter().enumerate() {
+            let chars = line.chars().collect::<Vec<char>>();
+            let mut col = 0;
+
+            while col < chars.len() {
+                if chars[col].is_ascii_digit() {
+                    let col_from = col;
+                    let mut value = 0;
+
+                    while let Some(digit) = chars.get(col).and_then(|c| c.to_digit(10)) {
+                        value = value * 10 + digit;
+                        cells[(row, col)] = Some(numbers.len());
+                        col += 1;
+                    }
+
+                    numbers.push(PartNumber {
+                        row,
+                        col_from,
+                        col_to: col - 1,
+                        value,
+                    });
+                } else {
+                    if chars[col] != '.' {
+                        symbols.insert((row, col), chars[col]);
+                    }
+                    col += 1;
+                }
+            }
+        }
+
+        let mut grid = Self {
+            cells,
+            numbers,
+            symbols,
+            touching: HashMap::new(),
+        };
+
+        grid.touching = grid
+            .symbols
+            .keys()
+            .map(|&(row, col)| ((row, col), grid.adjacent_numbers(row, col)))
+            .collect();
+
+        grid
+    }
+
+    #[must_use]
+    pub fn number_at(&self, row: usize, col: usize) -> Option<&PartNumber> {
+        self.cell(row, col).map(|id| &self.numbers[id])
+    }
+
+    #[must_use]
+    pub fn symbol_at(&self, row: usize, col: usize) -> Option<char> {
+        self.symbols.get(&(row, col)).copied()
+    }
+
+    #[must_use]
+    pub fn parts_touching(&self, row: usize, col: usize) -> Vec<&PartNumber> {
+        self.touching
+            .get(&(row, col))
+            .map(|ids| ids.iter().map(|id| &self.numbers[*id]).collect())
+            .unwrap_or_default()
+    }
+
+    #[must_use]
+    pub fn part_numbers(&self) -> Vec<&PartNumber> {
+        let mut ids = self
+            .touching
+            .values()
+            .flatten()
+            .copied()
+            .collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+
+        ids.into_iter().map(|id| &self.numbers[id]).collect()
+    }
+
+    #[must_use]
+    pub fn gears(&self, symbol: char, neighbours: usize) -> Vec<Gear> {
+        let mut gears = self
+            .touching
+            .iter()
+            .filter(|(position, ids)| self.symbols[*position] == symbol && ids.len() == neighbours)
+            .map(|(&(row, col), ids)| Gear {
+                row,
+                col,
+                numbers: ids.iter().map(|id| self.numbers[*id].value).collect(),
+            })
+            .collect::<Vec<_>>();
+
+        gears.sort_by_key(|gear| (gear.row, gear.col));
+        gears
+    }
+
+    #[must_use]
+    pub fn gear_ratio_sum(&self, symbol: char, neighbours: usize) -> u64 {
+        self.gears(symbol, neighbours)
+            .iter()
+            .map(|gear| gear.numbers.iter().map(|x| u64::from(*x)).product::<u64>())
+            .sum()
+    }
+
+    fn cell(&self, row: usize, col: usize) -> Option<usize> {
+        self.cells.get((row, col)).copied().flatten()
+    }
+
+    fn adjacent_numbers(&self, row: usize, col: usize) -> Vec<usize> {
+        let mut ids = self
+            .cells
+            .neighbours8((row, col))
+            .filter_map(|(r, c)| self.cell(r, c))
+            .collect::<Vec<_>>();
+
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
 #[test]
 fn should_extract_numbers_from_a_line() {
     let input = "467......2";
@@ -312,3 +465,42 @@ fn should_find_numbers_per_gear() {
         )])
     );
 }
+
+#[test]
+fn should_index_numbers_by_cell() {
+    let grid = EngineGrid::new(&["467..114..", "...*......", "..35..633."]);
+
+    assert_eq!(grid.number_at(0, 1).map(|n| n.value), Some(467));
+    assert_eq!(grid.number_at(0, 3), None);
+    assert_eq!(grid.symbol_at(1, 3), Some('*'));
+    assert_eq!(
+        grid.parts_touching(1, 3)
+            .iter()
+            .map(|n| n.value)
+            .collect::<Vec<_>>(),
+        vec![467, 35]
+    );
+}
+
+#[test]
+fn should_support_any_symbol_and_gear_rule() {
+    let grid = EngineGrid::new(&["12.3", "..!.", "4^..", ".5.."]);
+
+    assert_eq!(
+        grid.part_numbers()
+            .iter()
+            .map(|n| n.value)
+            .collect::<Vec<_>>(),
+        vec![12, 3, 4, 5]
+    );
+    assert_eq!(
+        grid.gears('!', 2),
+        vec![Gear {
+            row: 1,
+            col: 2,
+            numbers: vec![12, 3],
+        }]
+    );
+    assert_eq!(grid.gear_ratio_sum('^', 2), 4 * 5);
+    assert_eq!(grid.gear_ratio_sum('^', 3), 0);
+}
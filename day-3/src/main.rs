@@ -11,7 +11,8 @@ fn main() {
 
     println!("Trying to solve part 2...");
     let before = std::time::Instant::now();
-    let sum_of_gear_ratios = day_3::sum_of_all_gear_ratios(input.lines().collect());
+    let grid = day_3::EngineGrid::new(&input.lines().collect::<Vec<_>>());
+    let sum_of_gear_ratios = grid.gear_ratio_sum('*', 2);
 
     let after = std::time::Instant::now();
     println!("Time taken: {:?}", after.duration_since(before));
@@ -39,3 +39,26 @@ fn should_calculate_gear_ratios() {
 
     assert_eq!(sum_of_all_gear_ratios, 467835);
 }
+
+#[test]
+fn should_solve_both_parts_with_engine_grid() {
+    let input = [
+        "467..114..",
+        "...*......",
+        "..35..633.",
+        "......#...",
+        "617*......",
+        ".....+.58.",
+        "..592.....",
+        "......755.",
+        "...$.*....",
+        ".664.598..",
+    ];
+
+    let grid = day_3::EngineGrid::new(&input);
+
+    let sum_of_part_numbers = grid.part_numbers().iter().map(|n| n.value).sum::<u32>();
+
+    assert_eq!(sum_of_part_numbers, 4361);
+    assert_eq!(grid.gear_ratio_sum('*', 2), 467_835);
+}
@@ -1,6 +1,42 @@
+use std::collections::HashSet;
+
 use lazy_static::lazy_static;
 use regex::Regex;
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CardRecord {
+    pub id: u32,
+    pub matches: u32,
+    pub copies: u32,
+    pub points: u32,
+}
+
+pub trait ScoringRule {
+    fn points(&self, matches: u32) -> u32;
+
+    fn copies_won(&self, card: usize, matches: u32, cards: usize) -> Vec<usize> {
+        CopyWindow::Clamp.targets(card, matches, cards)
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum CopyWindow {
+    #[default]
+    Clamp,
+    Wrap,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Doubling {
+    pub window: CopyWindow,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Linear {
+    pub per_match: u32,
+    pub window: CopyWindow,
+}
+
 struct Game {
     id: u32,
     winning_numbers: Vec<u32>,
@@ -23,43 +59,99 @@ impl Game {
 }
 
 pub fn calculate_points(input: Vec<&str>) -> u32 {
+    calculate_points_with(input, &Doubling::default())
+}
+
+pub fn calculate_points_with(input: Vec<&str>, rule: &dyn ScoringRule) -> u32 {
     input
         .iter()
         .map(|x| Game::from(x))
-        .map(|x| winners(x.winning_numbers, x.numbers))
-        .map(calculate_score)
+        .map(|x| rule.points(winners(x.winning_numbers, x.numbers).len() as u32))
         .sum()
 }
 
 pub fn calculate_scratchcards(input: Vec<&str>) -> u32 {
-    let winners: Vec<u32> = input
+    calculate_scratchcards_with(input, &Doubling::default())
+}
+
+pub fn calculate_scratchcards_with(input: Vec<&str>, rule: &dyn ScoringRule) -> u32 {
+    card_records_with(input, rule)
+        .iter()
+        .map(|x| x.copies)
+        .sum()
+}
+
+pub fn card_records(input: Vec<&str>) -> Vec<CardRecord> {
+    card_records_with(input, &Doubling::default())
+}
+
+// Cards are settled in order, so copies that wrap around to a card that was
+// already settled are counted but do not win anything themselves. Otherwise a
+// single winning card near the end could cascade forever.
+pub fn card_records_with(input: Vec<&str>, rule: &dyn ScoringRule) -> Vec<CardRecord> {
+    let mut records = input
         .iter()
         .map(|x| Game::from(x))
-        .map(|x| winners(x.winning_numbers, x.numbers).len() as u32)
-        .collect();
+        .map(|x| {
+            let matches = winners(x.winning_numbers, x.numbers).len() as u32;
 
-    (0..winners.len() as u32)
-        .map(|i| calculate_cards(i, 0, &winners))
-        .sum()
+            CardRecord {
+                id: x.id,
+                matches,
+                copies: 1,
+                points: rule.points(matches),
+            }
+        })
+        .collect::<Vec<CardRecord>>();
+
+    for i in 0..records.len() {
+        let copies = records[i].copies;
+
+        for j in rule.copies_won(i, records[i].matches, records.len()) {
+            records[j].copies += copies;
+        }
+    }
+
+    records
 }
 
-fn calculate_cards(pos: u32, acc: u32, winners: &Vec<u32>) -> u32 {
-    match winners.get(pos as usize) {
-        Some(x) => (1..=*x)
-            .map(|j| calculate_cards(pos + j, acc + x, winners))
-            .fold(1, |total, x| total + x),
-        None => acc,
+impl CopyWindow {
+    pub fn targets(self, card: usize, matches: u32, cards: usize) -> Vec<usize> {
+        let window = card + 1..=card + matches as usize;
+
+        match self {
+            CopyWindow::Clamp => window.take_while(|x| *x < cards).collect(),
+            CopyWindow::Wrap => window.map(|x| x % cards).collect(),
+        }
     }
 }
 
-fn calculate_score(winners: Vec<u32>) -> u32 {
-    winners
-        .is_empty()
-        .then_some(0)
-        .unwrap_or_else(|| 2u32.pow((winners.len() - 1) as u32))
+impl ScoringRule for Doubling {
+    fn points(&self, matches: u32) -> u32 {
+        match matches {
+            0 => 0,
+            n => 2u32.pow(n - 1),
+        }
+    }
+
+    fn copies_won(&self, card: usize, matches: u32, cards: usize) -> Vec<usize> {
+        self.window.targets(card, matches, cards)
+    }
+}
+
+impl ScoringRule for Linear {
+    fn points(&self, matches: u32) -> u32 {
+        matches * self.per_match
+    }
+
+    fn copies_won(&self, card: usize, matches: u32, cards: usize) -> Vec<usize> {
+        self.window.targets(card, matches, cards)
+    }
 }
 
 fn winners(winning_numbers: Vec<u32>, numbers: Vec<u32>) -> Vec<u32> {
+    let numbers = numbers.into_iter().collect::<HashSet<u32>>();
+
     winning_numbers
         .into_iter()
         .filter(|x| numbers.contains(x))
@@ -115,9 +207,9 @@ fn should_filter_winning_numbers() {
 
 #[test]
 fn should_calculate_score() {
-    let winners = vec![48, 83, 86, 17];
+    let winners = [48, 83, 86, 17];
 
-    let score = calculate_score(winners);
+    let score = Doubling::default().points(winners.len() as u32);
 
     assert_eq!(score, 8);
 }
@@ -132,3 +224,49 @@ fn should_create_a_game() {
     assert_eq!(game.winning_numbers, vec![41, 48, 83, 86, 17]);
     assert_eq!(game.numbers, vec![83, 86, 6, 31, 17, 9, 48, 53]);
 }
+
+#[test]
+fn should_create_card_records() {
+    let input = vec![
+        "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+        "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+        "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+        "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+        "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+        "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    ];
+
+    let records = card_records(input);
+
+    assert_eq!(
+        records
+            .iter()
+            .map(|x| (x.id, x.matches, x.copies, x.points))
+            .collect::<Vec<_>>(),
+        vec![
+            (1, 4, 1, 8),
+            (2, 2, 2, 2),
+            (3, 2, 4, 2),
+            (4, 1, 8, 1),
+            (5, 0, 14, 0),
+            (6, 0, 1, 0),
+        ]
+    );
+}
+
+#[test]
+fn should_score_with_linear_rule() {
+    let rule = Linear {
+        per_match: 3,
+        window: CopyWindow::Clamp,
+    };
+
+    assert_eq!(rule.points(0), 0);
+    assert_eq!(rule.points(4), 12);
+}
+
+#[test]
+fn should_pick_copy_window() {
+    assert_eq!(CopyWindow::Clamp.targets(4, 3, 6), vec![5]);
+    assert_eq!(CopyWindow::Wrap.targets(4, 3, 6), vec![5, 0, 1]);
+}
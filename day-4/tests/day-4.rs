@@ -29,3 +29,43 @@ fn should_calculate_total_scratchcards() {
 
     assert_eq!(scratchcards, 30);
 }
+
+#[test]
+fn should_calculate_totals_with_house_rules() {
+    let input = vec![
+        "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53",
+        "Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19",
+        "Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1",
+        "Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83",
+        "Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36",
+        "Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+    ];
+
+    let linear = day_4::Linear {
+        per_match: 1,
+        window: day_4::CopyWindow::Clamp,
+    };
+    let wrap = day_4::Doubling {
+        window: day_4::CopyWindow::Wrap,
+    };
+
+    assert_eq!(day_4::calculate_points_with(input.clone(), &linear), 9);
+    assert_eq!(
+        day_4::calculate_scratchcards_with(input.clone(), &linear),
+        30
+    );
+    assert_eq!(day_4::calculate_scratchcards_with(input, &wrap), 30);
+
+    // Only the last card wins, so its two copies run past the end.
+    let input = vec![
+        "Card 1:  1  2 |  3  4",
+        "Card 2:  1  2 |  3  4",
+        "Card 3:  1  2 |  1  2",
+    ];
+    let clamp = day_4::Doubling {
+        window: day_4::CopyWindow::Clamp,
+    };
+
+    assert_eq!(day_4::calculate_scratchcards_with(input.clone(), &clamp), 3);
+    assert_eq!(day_4::calculate_scratchcards_with(input, &wrap), 5);
+}
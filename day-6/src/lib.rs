@@ -7,40 +7,144 @@ lazy_static! {
     static ref NUMBER_REGEX: Regex = Regex::new(r"\d+").unwrap();
 }
 
-pub fn records(input: Vec<&str>) -> u64 {
-    parse_document(input)
-        .iter()
-        .map(|x| number_of_records(*x) as u64)
-        .product()
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Race {
+    pub time: u128,
+    pub distance: u128,
 }
 
-fn number_of_records(input: (u64, u64)) -> usize {
-    let (time, dist) = input;
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum DocumentError {
+    MissingLine(&'static str),
+    CountMismatch { times: usize, distances: usize },
+    InvalidNumber(String),
+    Overflow,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Document {
+    times: Vec<String>,
+    distances: Vec<String>,
+}
+
+pub fn records(input: Vec<&str>) -> Result<u128, DocumentError> {
+    parse_document(input)?
+        .into_iter()
+        .try_fold(1_u128, |acc, x| {
+            acc.checked_mul(number_of_records(x)?)
+                .ok_or(DocumentError::Overflow)
+        })
+}
+
+pub fn records_kerned(input: Vec<&str>) -> Result<u128, DocumentError> {
+    Document::parse(&input)?.kerned()?.ways()
+}
+
+impl Document {
+    pub fn parse(input: &[&str]) -> Result<Self, DocumentError> {
+        let time = input.first().ok_or(DocumentError::MissingLine("Time"))?;
+        let dist = input.get(1).ok_or(DocumentError::MissingLine("Distance"))?;
+
+        let times = parse_digits(time);
+        let distances = parse_digits(dist);
+
+        if times.len() != distances.len() {
+            return Err(DocumentError::CountMismatch {
+                times: times.len(),
+                distances: distances.len(),
+            });
+        }
+
+        Ok(Self { times, distances })
+    }
+
+    pub fn separate(&self) -> Result<Vec<Race>, DocumentError> {
+        zip(&self.times, &self.distances)
+            .map(|(time, dist)| Ok(Race::new(parse_number(time)?, parse_number(dist)?)))
+            .collect()
+    }
 
-    (1..time).filter(|i| (time - i) * i > dist).count()
+    pub fn kerned(&self) -> Result<Race, DocumentError> {
+        Ok(Race::new(
+            parse_number(&self.times.concat())?,
+            parse_number(&self.distances.concat())?,
+        ))
+    }
 }
 
-fn parse_document(input: Vec<&str>) -> Vec<(u64, u64)> {
-    let time = parse_line(input.first().unwrap());
-    let dist = parse_line(input.get(1).unwrap());
+impl Race {
+    pub fn new(time: u128, distance: u128) -> Self {
+        Self { time, distance }
+    }
 
-    zip(time, dist).collect()
+    // Split the time into halves a = t / 2 and b = t - a and hold for a - k.
+    // The boat then travels (a - k) * (b + k) = ab - k * (b - a) - k^2, so it
+    // wins while k^2 + k * (b - a) < ab - d. Working from the halves keeps the
+    // interval exact up to t < 2^65 with an integer square root instead of
+    // floats, and longer races report an overflow.
+    pub fn hold_interval(&self) -> Result<Option<(u128, u128)>, DocumentError> {
+        let a = self.time / 2;
+        let b = self.time - a;
+
+        let best = a.checked_mul(b).ok_or(DocumentError::Overflow)?;
+        let Some(margin) = best
+            .checked_sub(self.distance)
+            .and_then(|x| x.checked_sub(1))
+        else {
+            return Ok(None);
+        };
+
+        let root = margin.isqrt();
+        let reach = match b - a {
+            0 => root,
+            _ if root * (root + 1) <= margin => root,
+            _ => root - 1,
+        };
+
+        Ok(Some((a - reach, b + reach)))
+    }
+
+    pub fn ways(&self) -> Result<u128, DocumentError> {
+        Ok(self
+            .hold_interval()?
+            .map_or(0, |(min_hold, max_hold)| max_hold - min_hold + 1))
+    }
 }
 
-fn parse_line(input: &str) -> Vec<u64> {
+fn number_of_records(input: (u128, u128)) -> Result<u128, DocumentError> {
+    let (time, dist) = input;
+
+    Race::new(time, dist).ways()
+}
+
+fn parse_document(input: Vec<&str>) -> Result<Vec<(u128, u128)>, DocumentError> {
+    Ok(Document::parse(&input)?
+        .separate()?
+        .into_iter()
+        .map(|x| (x.time, x.distance))
+        .collect())
+}
+
+fn parse_digits(input: &str) -> Vec<String> {
     NUMBER_REGEX
         .find_iter(input)
-        .map(|x| x.as_str().parse::<u64>().unwrap())
+        .map(|x| x.as_str().to_string())
         .collect()
 }
 
+fn parse_number(input: &str) -> Result<u128, DocumentError> {
+    input
+        .parse::<u128>()
+        .map_err(|_| DocumentError::InvalidNumber(input.to_string()))
+}
+
 #[test]
 fn should_parse_document() {
     let input = vec!["Time:      7  15   30", "Distance:  9  40  200"];
 
     let doc = parse_document(input);
 
-    assert_eq!(doc, vec![(7, 9), (15, 40), (30, 200)]);
+    assert_eq!(doc, Ok(vec![(7, 9), (15, 40), (30, 200)]));
 }
 
 #[test]
@@ -49,5 +153,86 @@ fn should_calculate_the_number_of_ways_to_beat_the_record() {
 
     let number_of_records = number_of_records(input);
 
-    assert_eq!(number_of_records, 4);
+    assert_eq!(number_of_records, Ok(4));
+}
+
+#[test]
+fn should_find_hold_interval() {
+    assert_eq!(Race::new(7, 9).hold_interval(), Ok(Some((2, 5))));
+    assert_eq!(Race::new(30, 200).hold_interval(), Ok(Some((11, 19))));
+    assert_eq!(Race::new(71530, 940200).ways(), Ok(71503));
+}
+
+#[test]
+fn should_handle_races_that_cannot_be_won() {
+    assert_eq!(Race::new(4, 4).hold_interval(), Ok(None));
+    assert_eq!(Race::new(3, 10).ways(), Ok(0));
+    assert_eq!(Race::new(0, 0).ways(), Ok(0));
+    assert_eq!(Race::new(5, u128::MAX).ways(), Ok(0));
+}
+
+#[test]
+fn should_solve_races_beyond_u64() {
+    let time = 10_000_000_000_000_000_000u128;
+
+    assert_eq!(Race::new(time, 0).ways(), Ok(time - 1));
+
+    let longest = (1 << 65) - 1;
+    let best = u128::MAX - u128::from(u64::MAX);
+
+    assert_eq!(Race::new(longest, 0).ways(), Ok(longest - 1));
+    assert_eq!(Race::new(longest, best - 1).ways(), Ok(2));
+    assert_eq!(Race::new(longest, best).ways(), Ok(0));
+    assert_eq!(Race::new(1 << 65, 0).ways(), Err(DocumentError::Overflow));
+}
+
+#[test]
+fn should_match_brute_force_for_small_races() {
+    for time in 0..40u128 {
+        for distance in 0..time * time / 4 + 2 {
+            let expected = (0..=time).filter(|h| h * (time - h) > distance).count();
+
+            assert_eq!(Race::new(time, distance).ways(), Ok(expected as u128));
+        }
+    }
+}
+
+#[test]
+fn should_read_document_both_ways() {
+    let input = ["Time:      7  15   30", "Distance:  9  40  200"];
+
+    let doc = Document::parse(&input).unwrap();
+
+    assert_eq!(
+        doc.separate(),
+        Ok(vec![Race::new(7, 9), Race::new(15, 40), Race::new(30, 200)])
+    );
+    assert_eq!(doc.kerned(), Ok(Race::new(71530, 940200)));
+}
+
+#[test]
+fn should_reject_malformed_documents() {
+    assert_eq!(
+        Document::parse(&["Time:      7  15   30", "Distance:  9  40"]),
+        Err(DocumentError::CountMismatch {
+            times: 3,
+            distances: 2
+        })
+    );
+    assert_eq!(
+        Document::parse(&["Time:      7"]),
+        Err(DocumentError::MissingLine("Distance"))
+    );
+
+    let huge = "9".repeat(40);
+    let doc = Document::parse(&[&format!("Time: {huge}"), "Distance: 1"]).unwrap();
+
+    assert_eq!(doc.kerned(), Err(DocumentError::InvalidNumber(huge)));
+    assert_eq!(
+        records(vec!["Time: 7 15", "Distance: 9"]),
+        Err(DocumentError::CountMismatch {
+            times: 2,
+            distances: 1
+        })
+    );
 }
@@ -4,5 +4,14 @@ fn should_caculate_records() {
 
     let result = day_6::records(input);
 
-    assert_eq!(result, 288);
+    assert_eq!(result, Ok(288));
+}
+
+#[test]
+fn should_calculate_kerned_record() {
+    let input = vec!["Time:      7  15   30", "Distance:  9  40  200"];
+
+    let result = day_6::records_kerned(input);
+
+    assert_eq!(result, Ok(71503));
 }
@@ -1,13 +1,21 @@
-use std::{cmp::Ordering, collections::HashMap};
+use std::collections::HashMap;
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Rules {
+    order: Vec<char>,
+    wildcards: Vec<char>,
+}
 
 #[derive(Debug, PartialEq, Clone, PartialOrd, Ord, Eq, Hash)]
-struct Hand {
+pub struct Hand {
+    key: u64,
+    hand_type: HandType,
     cards: String,
     bid: u32,
 }
 
-#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Hash)]
-enum HandType {
+#[derive(Debug, PartialEq, PartialOrd, Ord, Eq, Clone, Copy, Hash)]
+pub enum HandType {
     HighCard,
     OnePair,
     TwoPair,
@@ -17,110 +25,285 @@ enum HandType {
     FiveOfAKind,
 }
 
-impl Hand {
-    fn new(input: &str) -> Self {
-        let cards = input.split(' ').next().unwrap();
-        let bid = input.split(' ').nth(1).unwrap().parse::<u32>().unwrap();
-
-        Self {
-            cards: cards.to_string(),
-            bid,
-        }
-    }
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum HandError {
+    MissingBid(String),
+    InvalidBid(String),
+    Length(usize),
+    UnknownCard(char),
 }
 
-pub fn calculate_total_winnings(input: Vec<&str>) -> u32 {
-    let mut hands = input.iter().map(|x| Hand::new(x)).collect::<Vec<Hand>>();
-
-    hands.sort_by(|a, b| sort_hand_type(a.clone(), b.clone()).unwrap());
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Standing {
+    pub hand: String,
+    pub bid: u32,
+    pub hand_type: HandType,
+    pub best_hand: String,
+    pub rank: u32,
+    pub winnings: u32,
+}
 
-    hands.iter().enumerate().fold(0, |mut acc, (i, x)| {
-        acc += x.bid * (i + 1) as u32;
-        acc
-    })
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Decider {
+    HandType {
+        above: HandType,
+        below: HandType,
+    },
+    Card {
+        position: usize,
+        above: char,
+        below: char,
+    },
+    Tie,
 }
 
-pub fn calculate_total_winnings_with_joker(input: Vec<&str>) -> u32 {
-    let mut hands = input.iter().map(|x| Hand::new(x)).collect::<Vec<Hand>>();
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Explanation {
+    pub above: String,
+    pub below: String,
+    pub decider: Decider,
+}
 
-    hands.sort_by(|a, b| sort_hand_type_with_joker(a.clone(), b.clone()).unwrap());
+pub fn calculate_total_winnings(input: Vec<&str>) -> Result<u32, HandError> {
+    total_winnings(input, &Rules::standard())
+}
 
-    hands.iter().enumerate().fold(0, |mut acc, (i, x)| {
-        acc += x.bid * (i + 1) as u32;
-        acc
-    })
+pub fn calculate_total_winnings_with_joker(input: Vec<&str>) -> Result<u32, HandError> {
+    total_winnings(input, &Rules::joker())
 }
 
-fn sort_hand_type_with_joker(first: Hand, second: Hand) -> Option<Ordering> {
-    let first_hand_type = max_hand_type_with_joker(first.clone());
-    let second_hand_type = max_hand_type_with_joker(second.clone());
+pub fn total_winnings(input: Vec<&str>, rules: &Rules) -> Result<u32, HandError> {
+    Ok(standings(input, rules)?.iter().map(|x| x.winnings).sum())
+}
 
-    first_hand_type
-        .partial_cmp(&second_hand_type)
-        .and_then(|x| match x {
-            Ordering::Equal => sort_cards(first, second, "J23456789TQKA"),
-            _ => Some(x),
+pub fn standings(input: Vec<&str>, rules: &Rules) -> Result<Vec<Standing>, HandError> {
+    let mut hands = input
+        .iter()
+        .map(|x| rules.hand(x))
+        .collect::<Result<Vec<Hand>, HandError>>()?;
+
+    hands.sort_by_key(|x| x.key);
+
+    Ok(hands
+        .into_iter()
+        .enumerate()
+        .map(|(i, x)| Standing {
+            best_hand: rules.best_hand(&x.cards),
+            hand_type: x.hand_type,
+            rank: i as u32 + 1,
+            winnings: x.bid * (i as u32 + 1),
+            bid: x.bid,
+            hand: x.cards,
         })
+        .collect())
 }
 
-fn sort_hand_type(first: Hand, second: Hand) -> Option<Ordering> {
-    let first_hand_type = hand_type(first.clone());
-    let second_hand_type = hand_type(second.clone());
+impl Rules {
+    // `order` lists the cards from weakest to strongest. Wildcards take their
+    // strength from `order` when breaking ties, so a joker that should lose
+    // ties goes first.
+    pub fn new(order: &str, wildcards: &str) -> Self {
+        Self {
+            order: order.chars().collect(),
+            wildcards: wildcards.chars().collect(),
+        }
+    }
+
+    pub fn standard() -> Self {
+        Self::new("23456789TJQKA", "")
+    }
 
-    first_hand_type
-        .partial_cmp(&second_hand_type)
-        .and_then(|x| match x {
-            Ordering::Equal => sort_cards(first, second, "23456789TJQKA"),
-            _ => Some(x),
-        })
+    pub fn joker() -> Self {
+        Self::new("J23456789TQKA", "J")
+    }
+
+    pub fn strength(&self, card: char) -> Option<u8> {
+        self.order.iter().position(|x| *x == card).map(|x| x as u8)
+    }
+
+    pub fn is_wildcard(&self, card: char) -> bool {
+        self.wildcards.contains(&card)
+    }
+
+    pub fn hand(&self, input: &str) -> Result<Hand, HandError> {
+        let (cards, bid) = input
+            .split_once(' ')
+            .ok_or_else(|| HandError::MissingBid(input.to_string()))?;
+        let bid = bid
+            .parse::<u32>()
+            .map_err(|_| HandError::InvalidBid(bid.to_string()))?;
+
+        Hand::new(cards, bid, self)
+    }
+
+    // Every wildcard becomes the most frequent regular card, preferring the
+    // stronger one on equal counts. A hand of only wildcards becomes the
+    // strongest regular card.
+    pub fn best_hand(&self, cards: &str) -> String {
+        let counts =
+            cards
+                .chars()
+                .filter(|x| !self.is_wildcard(*x))
+                .fold(HashMap::new(), |mut acc, x| {
+                    *acc.entry(x).or_insert(0) += 1;
+                    acc
+                });
+
+        let substitute = counts
+            .into_iter()
+            .max_by_key(|(card, count)| (*count, self.strength(*card)))
+            .map(|(card, _)| card)
+            .or_else(|| {
+                self.order
+                    .iter()
+                    .rev()
+                    .find(|x| !self.is_wildcard(**x))
+                    .copied()
+            });
+
+        match substitute {
+            Some(substitute) => cards
+                .chars()
+                .map(|x| match self.is_wildcard(x) {
+                    true => substitute,
+                    false => x,
+                })
+                .collect(),
+            None => cards.to_string(),
+        }
+    }
+
+    pub fn explain(&self, first: &Hand, second: &Hand) -> Explanation {
+        let (above, below) = match first.key >= second.key {
+            true => (first, second),
+            false => (second, first),
+        };
+
+        let decider = match above.hand_type != below.hand_type {
+            true => Decider::HandType {
+                above: above.hand_type,
+                below: below.hand_type,
+            },
+            false => above
+                .cards
+                .chars()
+                .zip(below.cards.chars())
+                .enumerate()
+                .find(|(_, (a, b))| self.strength(*a) != self.strength(*b))
+                .map_or(Decider::Tie, |(position, (above, below))| Decider::Card {
+                    position,
+                    above,
+                    below,
+                }),
+        };
+
+        Explanation {
+            above: above.cards.clone(),
+            below: below.cards.clone(),
+            decider,
+        }
+    }
+
+    pub fn best_type(&self, cards: &str) -> HandType {
+        let mut counts = cards
+            .chars()
+            .filter(|x| !self.is_wildcard(*x))
+            .fold(HashMap::new(), |mut acc, x| {
+                *acc.entry(x).or_insert(0) += 1;
+                acc
+            })
+            .into_values()
+            .collect::<Vec<usize>>();
+        counts.sort_unstable_by(|a, b| b.cmp(a));
+
+        let wildcards = cards.chars().filter(|x| self.is_wildcard(*x)).count();
+
+        match counts.first_mut() {
+            Some(first) => *first += wildcards,
+            None => counts.push(wildcards),
+        }
+
+        match (counts[0], counts.get(1).copied().unwrap_or(0)) {
+            (5.., _) => HandType::FiveOfAKind,
+            (4, _) => HandType::FourOfAKind,
+            (3, 2) => HandType::FullHouse,
+            (3, _) => HandType::ThreeOfAKind,
+            (2, 2) => HandType::TwoPair,
+            (2, _) => HandType::OnePair,
+            _ => HandType::HighCard,
+        }
+    }
+}
+
+impl Explanation {
+    pub fn describe(&self) -> String {
+        match &self.decider {
+            Decider::HandType { above, below } => format!(
+                "{} is above {} because {:?} beats {:?}",
+                self.above, self.below, above, below
+            ),
+            Decider::Card {
+                position,
+                above,
+                below,
+            } => format!(
+                "{} is above {} because both are the same type and card {} is {} against {}",
+                self.above,
+                self.below,
+                position + 1,
+                above,
+                below
+            ),
+            Decider::Tie => format!("{} and {} are tied", self.above, self.below),
+        }
+    }
 }
 
-fn max_hand_type_with_joker(hand: Hand) -> HandType {
-    "23456789TJQKA"
-        .chars()
-        .map(|x| {
-            let mut hand = hand.clone();
-            hand.cards = hand.cards.replace('J', &x.to_string());
-            hand_type(hand)
+impl Hand {
+    // One byte for the hand type and one per card have to fit in the u64 key.
+    pub const MAX_CARDS: usize = 7;
+
+    // The key packs the hand type above one byte per card, so comparing keys
+    // orders hands by type first and then card by card.
+    pub fn new(cards: &str, bid: u32, rules: &Rules) -> Result<Self, HandError> {
+        let strengths = cards
+            .chars()
+            .map(|x| rules.strength(x).ok_or(HandError::UnknownCard(x)))
+            .collect::<Result<Vec<u8>, HandError>>()?;
+
+        if strengths.is_empty() || strengths.len() > Self::MAX_CARDS {
+            return Err(HandError::Length(strengths.len()));
+        }
+
+        let hand_type = rules.best_type(cards);
+
+        let key = strengths
+            .iter()
+            .fold(hand_type as u64, |acc, x| acc << 8 | *x as u64)
+            << (8 * (Self::MAX_CARDS - strengths.len()));
+
+        Ok(Self {
+            key,
+            hand_type,
+            cards: cards.to_string(),
+            bid,
         })
-        .max()
-        .unwrap()
-}
-
-fn sort_cards(first: Hand, second: Hand, card_order: &str) -> Option<Ordering> {
-    let first_card = first
-        .cards
-        .chars()
-        .map(|x| card_order.find(x).unwrap())
-        .collect::<Vec<usize>>();
-
-    let second_card = second
-        .cards
-        .chars()
-        .map(|x| card_order.find(x).unwrap())
-        .collect::<Vec<usize>>();
-
-    Some(first_card.partial_cmp(&second_card).unwrap())
-}
-
-fn hand_type(hand: Hand) -> HandType {
-    let counts = hand.cards.chars().fold(HashMap::new(), |mut acc, x| {
-        *acc.entry(x).or_insert(0) += 1;
-        acc
-    });
-
-    match counts.values().max() {
-        Some(5) => HandType::FiveOfAKind,
-        Some(4) => HandType::FourOfAKind,
-        Some(3) => match counts.values().any(|x| *x == 2) {
-            true => HandType::FullHouse,
-            false => HandType::ThreeOfAKind,
-        },
-        Some(2) => match counts.values().filter(|x| **x == 2).count() == 2 {
-            true => HandType::TwoPair,
-            false => HandType::OnePair,
-        },
-        _ => HandType::HighCard,
+    }
+
+    pub fn key(&self) -> u64 {
+        self.key
+    }
+
+    pub fn hand_type(&self) -> HandType {
+        self.hand_type
+    }
+
+    pub fn cards(&self) -> &str {
+        &self.cards
+    }
+
+    pub fn bid(&self) -> u32 {
+        self.bid
     }
 }
 
@@ -128,173 +311,110 @@ fn hand_type(hand: Hand) -> HandType {
 fn should_parse_hand_with_one_pair() {
     let input = "32T3K 765";
 
-    let hand = Hand::new(input);
+    let hand = Rules::standard().hand(input).unwrap();
 
-    assert_eq!(
-        hand,
-        Hand {
-            cards: String::from("32T3K"),
-            bid: 765,
-        }
-    );
+    assert_eq!((hand.cards(), hand.bid()), ("32T3K", 765));
 
-    assert_eq!(hand_type(hand), HandType::OnePair);
+    assert_eq!(hand.hand_type(), HandType::OnePair);
 }
 
 #[test]
 fn should_parse_hand_with_two_pairs() {
     let input = "32K3K 765";
 
-    let hand = Hand::new(input);
+    let hand = Rules::standard().hand(input).unwrap();
 
-    assert_eq!(
-        hand,
-        Hand {
-            cards: String::from("32K3K"),
-            bid: 765,
-        }
-    );
+    assert_eq!((hand.cards(), hand.bid()), ("32K3K", 765));
 
-    assert_eq!(hand_type(hand), HandType::TwoPair);
+    assert_eq!(hand.hand_type(), HandType::TwoPair);
 }
 
 #[test]
 fn should_parse_hand_with_three_of_a_kind() {
     let input = "T55J5 65";
 
-    let hand = Hand::new(input);
+    let hand = Rules::standard().hand(input).unwrap();
 
-    assert_eq!(
-        hand,
-        Hand {
-            cards: String::from("T55J5"),
-            bid: 65,
-        }
-    );
+    assert_eq!((hand.cards(), hand.bid()), ("T55J5", 65));
 
-    assert_eq!(hand_type(hand), HandType::ThreeOfAKind);
+    assert_eq!(hand.hand_type(), HandType::ThreeOfAKind);
 }
 
 #[test]
 fn should_parse_hand_with_four_of_a_kind() {
     let input = "5555J 65";
 
-    let hand = Hand::new(input);
+    let hand = Rules::standard().hand(input).unwrap();
 
-    assert_eq!(
-        hand,
-        Hand {
-            cards: String::from("5555J"),
-            bid: 65,
-        }
-    );
+    assert_eq!((hand.cards(), hand.bid()), ("5555J", 65));
 
-    assert_eq!(hand_type(hand), HandType::FourOfAKind);
+    assert_eq!(hand.hand_type(), HandType::FourOfAKind);
 }
 
 #[test]
 fn should_parse_hand_with_five_of_a_kind() {
     let input = "55555 65";
 
-    let hand = Hand::new(input);
+    let hand = Rules::standard().hand(input).unwrap();
 
-    assert_eq!(
-        hand,
-        Hand {
-            cards: String::from("55555"),
-            bid: 65,
-        }
-    );
+    assert_eq!((hand.cards(), hand.bid()), ("55555", 65));
 
-    assert_eq!(hand_type(hand), HandType::FiveOfAKind);
+    assert_eq!(hand.hand_type(), HandType::FiveOfAKind);
 }
 
 #[test]
 fn should_parse_hand_with_full_house() {
     let input = "555JJ 65";
 
-    let hand = Hand::new(input);
+    let hand = Rules::standard().hand(input).unwrap();
 
-    assert_eq!(
-        hand,
-        Hand {
-            cards: String::from("555JJ"),
-            bid: 65,
-        }
-    );
+    assert_eq!((hand.cards(), hand.bid()), ("555JJ", 65));
 
-    assert_eq!(hand_type(hand), HandType::FullHouse);
+    assert_eq!(hand.hand_type(), HandType::FullHouse);
 }
 
 #[test]
 fn should_parse_hand_with_high_card() {
     let input = "AKQJT 65";
-    let hand = Hand::new(input);
+    let hand = Rules::standard().hand(input).unwrap();
 
-    assert_eq!(
-        hand,
-        Hand {
-            cards: String::from("AKQJT"),
-            bid: 65,
-        }
-    );
+    assert_eq!((hand.cards(), hand.bid()), ("AKQJT", 65));
 
-    assert_eq!(hand_type(hand), HandType::HighCard);
+    assert_eq!(hand.hand_type(), HandType::HighCard);
 }
 
 #[test]
 fn should_sort_hands_with_different_types() {
-    let full_house = Hand {
-        cards: String::from("555JJ"),
-        bid: 65,
-    };
+    let full_house = Rules::standard().hand("555JJ 65").unwrap();
 
-    let high_card = Hand {
-        cards: String::from("AKQJT"),
-        bid: 65,
-    };
+    let high_card = Rules::standard().hand("AKQJT 65").unwrap();
 
     let mut hands = vec![full_house.clone(), high_card.clone()];
-    hands.sort_by(|a, b| sort_hand_type(a.clone(), b.clone()).unwrap());
+    hands.sort_by_key(|x| x.key());
 
     assert_eq!(hands, vec![high_card, full_house]);
 }
 
 #[test]
 fn should_sort_hands_with_same_type_and_different_cards() {
-    let full_house_1 = Hand {
-        cards: String::from("555JJ"),
-        bid: 65,
-    };
+    let full_house_1 = Rules::standard().hand("555JJ 65").unwrap();
 
-    let full_house_2 = Hand {
-        cards: String::from("555TT"),
-        bid: 65,
-    };
+    let full_house_2 = Rules::standard().hand("555TT 65").unwrap();
 
     let mut hands = vec![full_house_1.clone(), full_house_2.clone()];
 
-    hands.sort_by(|a, b| sort_hand_type(a.clone(), b.clone()).unwrap());
+    hands.sort_by_key(|x| x.key());
 
     assert_eq!(hands, vec![full_house_2, full_house_1]);
 }
 
 #[test]
 fn should_sort_hands_with_high_cards() {
-    let high_card_six = Hand {
-        cards: String::from("AKQJT"),
-        bid: 65,
-    };
+    let high_card_six = Rules::standard().hand("AKQJT 65").unwrap();
 
-    let high_card_seven = Hand {
-        cards: String::from("AKQJT"),
-        bid: 65,
-    };
+    let high_card_seven = Rules::standard().hand("AKQJT 65").unwrap();
 
-    let high_card_eight = Hand {
-        cards: String::from("AKQJT"),
-        bid: 65,
-    };
+    let high_card_eight = Rules::standard().hand("AKQJT 65").unwrap();
 
     let mut hands = vec![
         high_card_six.clone(),
@@ -309,15 +429,9 @@ fn should_sort_hands_with_high_cards() {
 
 #[test]
 fn should_sort_hands_with_same_type() {
-    let four_of_a_kind_1 = Hand {
-        cards: String::from("5555J"),
-        bid: 65,
-    };
+    let four_of_a_kind_1 = Rules::standard().hand("5555J 65").unwrap();
 
-    let four_of_a_kind_2 = Hand {
-        cards: String::from("5555J"),
-        bid: 65,
-    };
+    let four_of_a_kind_2 = Rules::standard().hand("5555J 65").unwrap();
 
     let mut hands = vec![four_of_a_kind_1.clone(), four_of_a_kind_2.clone()];
 
@@ -328,15 +442,9 @@ fn should_sort_hands_with_same_type() {
 
 #[test]
 fn should_sort_hands_with_both_have_a_full_house() {
-    let full_house_1 = Hand {
-        cards: String::from("555JJ"),
-        bid: 65,
-    };
+    let full_house_1 = Rules::standard().hand("555JJ 65").unwrap();
 
-    let full_house_2 = Hand {
-        cards: String::from("555JJ"),
-        bid: 65,
-    };
+    let full_house_2 = Rules::standard().hand("555JJ 65").unwrap();
 
     let mut hands = vec![full_house_1.clone(), full_house_2.clone()];
 
@@ -347,10 +455,114 @@ fn should_sort_hands_with_both_have_a_full_house() {
 
 #[test]
 fn should_find_max_hand_type_with_joker() {
-    let hand = Hand {
-        cards: String::from("KTJJT"),
-        bid: 65,
-    };
+    let hand = Rules::joker().hand("KTJJT 65").unwrap();
 
-    assert_eq!(max_hand_type_with_joker(hand), HandType::FourOfAKind);
+    assert_eq!(hand.hand_type(), HandType::FourOfAKind);
+}
+
+#[test]
+fn should_order_keys_by_type_then_cards() {
+    let rules = Rules::standard();
+
+    assert!(rules.hand("22223 1").unwrap().key() < rules.hand("AAAAA 1").unwrap().key());
+    assert!(rules.hand("2AAAA 1").unwrap().key() < rules.hand("33332 1").unwrap().key());
+    assert!(rules.hand("KK677 1").unwrap().key() > rules.hand("KTJJT 1").unwrap().key());
+}
+
+#[test]
+fn should_apply_house_wildcards() {
+    let rules = Rules::new("JW23456789TQKA", "JW");
+
+    assert_eq!(
+        rules.hand("JW2JW 1").unwrap().hand_type(),
+        HandType::FiveOfAKind
+    );
+    assert_eq!(
+        rules.hand("JJJJJ 1").unwrap().hand_type(),
+        HandType::FiveOfAKind
+    );
+    assert_eq!(
+        rules.hand("23W45 1").unwrap().hand_type(),
+        HandType::OnePair
+    );
+    assert_eq!(
+        rules.hand("2233W 1").unwrap().hand_type(),
+        HandType::FullHouse
+    );
+    assert!(rules.hand("JJJJJ 1").unwrap().key() < rules.hand("22222 1").unwrap().key());
+}
+
+#[test]
+fn should_substitute_wildcards_in_best_hand() {
+    let rules = Rules::joker();
+
+    assert_eq!(rules.best_hand("KTJJT"), "KTTTT");
+    assert_eq!(rules.best_hand("QJJQ2"), "QQQQ2");
+    assert_eq!(rules.best_hand("JJJJJ"), "AAAAA");
+    assert_eq!(rules.best_hand("T55J5"), "T5555");
+    assert_eq!(Rules::standard().best_hand("KTJJT"), "KTJJT");
+}
+
+#[test]
+fn should_explain_which_rule_decided_the_order() {
+    let rules = Rules::joker();
+
+    let by_type = rules.explain(
+        &rules.hand("32T3K 1").unwrap(),
+        &rules.hand("KTJJT 1").unwrap(),
+    );
+    let by_card = rules.explain(
+        &rules.hand("QQQJA 1").unwrap(),
+        &rules.hand("T55J5 1").unwrap(),
+    );
+    let tie = rules.explain(
+        &rules.hand("QJJQ2 1").unwrap(),
+        &rules.hand("QJJQ2 1").unwrap(),
+    );
+
+    assert_eq!(
+        by_type.decider,
+        Decider::HandType {
+            above: HandType::FourOfAKind,
+            below: HandType::OnePair
+        }
+    );
+    assert_eq!(
+        by_card.decider,
+        Decider::Card {
+            position: 0,
+            above: 'Q',
+            below: 'T'
+        }
+    );
+    assert_eq!(
+        by_card.describe(),
+        "QQQJA is above T55J5 because both are the same type and card 1 is Q against T"
+    );
+    assert_eq!(tie.decider, Decider::Tie);
+}
+
+#[test]
+fn should_reject_invalid_hands() {
+    let rules = Rules::standard();
+
+    assert_eq!(
+        rules.hand("AAAAAAA 1").map(|x| x.hand_type()),
+        Ok(HandType::FiveOfAKind)
+    );
+    assert_eq!(rules.hand("AAAAAAAA 1"), Err(HandError::Length(8)));
+    assert_eq!(rules.hand(" 1"), Err(HandError::Length(0)));
+    assert_eq!(rules.hand("AAJA1 1"), Err(HandError::UnknownCard('1')));
+    assert_eq!(
+        rules.hand("AAAAA"),
+        Err(HandError::MissingBid(String::from("AAAAA")))
+    );
+    assert_eq!(
+        rules.hand("AAAAA x"),
+        Err(HandError::InvalidBid(String::from("x")))
+    );
+    assert_eq!(
+        standings(vec!["32T3K 765", "32T3Z 1"], &rules),
+        Err(HandError::UnknownCard('Z'))
+    );
 }
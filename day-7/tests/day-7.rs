@@ -10,5 +10,48 @@ fn should_calculate_winnings() {
 
     let winnings = day_7::calculate_total_winnings(input);
 
-    assert_eq!(winnings, 6440);
+    assert_eq!(winnings, Ok(6440));
+}
+
+#[test]
+fn should_calculate_winnings_with_joker() {
+    let input = vec![
+        "32T3K 765",
+        "T55J5 684",
+        "KK677 28",
+        "KTJJT 220",
+        "QQQJA 483",
+    ];
+
+    let winnings = day_7::calculate_total_winnings_with_joker(input);
+
+    assert_eq!(winnings, Ok(5905));
+}
+
+#[test]
+fn should_list_standings_with_joker() {
+    let input = vec![
+        "32T3K 765",
+        "T55J5 684",
+        "KK677 28",
+        "KTJJT 220",
+        "QQQJA 483",
+    ];
+
+    let standings = day_7::standings(input, &day_7::Rules::joker()).unwrap();
+
+    assert_eq!(
+        standings
+            .iter()
+            .map(|x| (x.hand.as_str(), x.best_hand.as_str(), x.rank, x.winnings))
+            .collect::<Vec<_>>(),
+        vec![
+            ("32T3K", "32T3K", 1, 765),
+            ("KK677", "KK677", 2, 56),
+            ("T55J5", "T5555", 3, 2052),
+            ("QQQJA", "QQQQA", 4, 1932),
+            ("KTJJT", "KTTTT", 5, 1100),
+        ]
+    );
+    assert_eq!(standings[4].hand_type, day_7::HandType::FourOfAKind);
 }
@@ -1,54 +1,433 @@
 use lazy_static::lazy_static;
-use num_integer::lcm;
+use num_integer::{lcm, Integer};
 use regex::Regex;
-use std::{collections::HashMap, iter::Cycle, str::Chars};
+use std::collections::HashMap;
 
-fn find_starting_nodes(network: HashMap<String, (String, String)>) -> Vec<String> {
-    network
-        .iter()
-        .filter(|(n, (_, _))| n.ends_with('A'))
-        .map(|(n, (_, _))| n.clone())
-        .collect::<Vec<String>>()
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Network {
+    names: Vec<String>,
+    ids: HashMap<String, u32>,
+    links: Vec<[u32; 2]>,
+    instructions: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeMatcher {
+    Exact(String),
+    Suffix(String),
+    Pattern(Regex),
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Route {
+    pub start: String,
+    pub steps: u64,
+    pub path: Option<Vec<String>>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum RouteError {
+    NeverReachesGoal { start: String, explored: u64 },
 }
 
+#[derive(Debug, Clone)]
+pub struct Walk<'a> {
+    network: &'a Network,
+    node: u32,
+    instruction: usize,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GhostCycle {
+    pub start: String,
+    pub offset: u64,
+    pub period: u64,
+    pub hits: Vec<u64>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct GhostSync {
+    pub ghosts: Vec<GhostCycle>,
+    pub steps: Option<u64>,
+    pub diagnostic: Option<String>,
+}
+
+// Combining every hit of every ghost multiplies the candidate congruences, so
+// give up with a diagnostic instead of exhausting memory.
+const MAX_CONGRUENCES: usize = 1 << 16;
+
 lazy_static! {
     static ref WORDS_REGEX: Regex = Regex::new(r"\w+").unwrap();
 }
 
 pub fn calculate_steps(input: Vec<&str>, start_node: &str, end_node: &str) -> u64 {
-    let mut directions = parse_directions(input.first().unwrap());
-    let network = create_network(input.get(2..).unwrap().to_vec());
+    let network = Network::compile(input);
+    let goal = NodeMatcher::Suffix(end_node.to_string());
+
+    network
+        .route(network.id(start_node).unwrap(), &goal, false)
+        .unwrap()
+        .steps
+}
+
+pub fn find_routes(
+    input: Vec<&str>,
+    start: &NodeMatcher,
+    goal: &NodeMatcher,
+    trace: bool,
+) -> Vec<Result<Route, RouteError>> {
+    let network = Network::compile(input);
+
+    network
+        .starts(start)
+        .into_iter()
+        .map(|x| network.route(x, goal, trace))
+        .collect()
+}
+
+pub fn calcualte_steps_with_multiple_starts(input: Vec<&str>) -> u64 {
+    let network = Network::compile(input);
+    let starting_nodes = network.starts(&NodeMatcher::Suffix("A".to_string()));
+
+    let steps = std::thread::scope(|scope| {
+        starting_nodes
+            .iter()
+            .map(|x| {
+                let network = &network;
+                let goal = NodeMatcher::Suffix("Z".to_string());
+                scope.spawn(move || network.route(*x, &goal, false).unwrap().steps)
+            })
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|x| x.join().unwrap())
+            .collect::<Vec<u64>>()
+    });
+
+    calculate_lcm(steps)
+}
+
+pub fn synchronise_ghosts(input: Vec<&str>) -> GhostSync {
+    synchronise_ghosts_with(
+        input,
+        &NodeMatcher::Suffix("A".to_string()),
+        &NodeMatcher::Suffix("Z".to_string()),
+    )
+}
+
+pub fn synchronise_ghosts_with(
+    input: Vec<&str>,
+    start: &NodeMatcher,
+    goal: &NodeMatcher,
+) -> GhostSync {
+    let network = Network::compile(input);
+
+    let goals = network.goals(goal);
+    let ghosts = network
+        .starts(start)
+        .into_iter()
+        .map(|x| analyse_ghost(&network, x, &goals))
+        .collect::<Vec<GhostCycle>>();
+
+    match common_step(&ghosts) {
+        Ok(steps) => GhostSync {
+            ghosts,
+            steps: Some(steps),
+            diagnostic: None,
+        },
+        Err(diagnostic) => GhostSync {
+            ghosts,
+            steps: None,
+            diagnostic: Some(diagnostic),
+        },
+    }
+}
+
+impl Network {
+    pub fn compile(input: Vec<&str>) -> Self {
+        let nodes = create_network(input.get(2..).unwrap().to_vec());
+
+        Self::from_nodes(input.first().unwrap(), &nodes)
+    }
+
+    // Walks cycle through the directions, so there has to be at least one.
+    fn from_nodes(directions: &str, nodes: &HashMap<String, (String, String)>) -> Self {
+        assert!(
+            !directions.is_empty(),
+            "there should be at least one direction"
+        );
+
+        let mut names = nodes.keys().cloned().collect::<Vec<String>>();
+        names.sort();
+
+        let ids = names
+            .iter()
+            .enumerate()
+            .map(|(i, x)| (x.clone(), i as u32))
+            .collect::<HashMap<String, u32>>();
+
+        let links = names
+            .iter()
+            .map(|x| {
+                let (left, right) = nodes.get(x).unwrap();
+                [*ids.get(left).unwrap(), *ids.get(right).unwrap()]
+            })
+            .collect();
+
+        let instructions = directions
+            .chars()
+            .map(|x| match x {
+                'L' => 0,
+                'R' => 1,
+                _ => panic!("Unknown direction"),
+            })
+            .collect();
+
+        Self {
+            names,
+            ids,
+            links,
+            instructions,
+        }
+    }
+
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: u32) -> &str {
+        &self.names[id as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn instructions(&self) -> &[u8] {
+        &self.instructions
+    }
+
+    pub fn step(&self, node: u32, instruction: usize) -> u32 {
+        self.links[node as usize][self.instructions[instruction] as usize]
+    }
+
+    pub fn walk(&self, start: u32) -> Walk<'_> {
+        Walk {
+            network: self,
+            node: start,
+            instruction: 0,
+        }
+    }
+
+    // Matches every node name once, so walks can test goals by id.
+    pub fn goals(&self, goal: &NodeMatcher) -> Vec<bool> {
+        self.names.iter().map(|x| goal.matches(x)).collect()
+    }
+
+    pub fn starts(&self, start: &NodeMatcher) -> Vec<u32> {
+        (0..self.names.len() as u32)
+            .filter(|x| start.matches(self.name(*x)))
+            .collect()
+    }
 
-    let mut steps = 0;
-    let mut current_node = start_node;
+    // Once every (node, instruction) state has been visited the walk repeats,
+    // so a goal that was not hit by then is never reached.
+    pub fn route(&self, start: u32, goal: &NodeMatcher, trace: bool) -> Result<Route, RouteError> {
+        let explored = (self.names.len() * self.instructions.len()) as u64;
+        let goals = self.goals(goal);
+        let mut path = trace.then(Vec::new);
 
-    while !current_node.ends_with(end_node) {
-        let (left, right) = network.get(current_node).unwrap();
-        let dir = directions.next().unwrap();
+        for (steps, (node, _)) in self.walk(start).take(explored as usize + 1).enumerate() {
+            if let Some(path) = path.as_mut() {
+                path.push(node);
+            }
 
-        current_node = match dir {
-            'L' => left,
-            'R' => right,
-            _ => panic!("Unknown direction"),
+            if goals[node as usize] {
+                return Ok(Route {
+                    start: self.name(start).to_string(),
+                    steps: steps as u64,
+                    path: path.map(|x| x.iter().map(|x| self.name(*x).to_string()).collect()),
+                });
+            }
+        }
+
+        Err(RouteError::NeverReachesGoal {
+            start: self.name(start).to_string(),
+            explored,
+        })
+    }
+}
+
+impl NodeMatcher {
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NodeMatcher::Exact(x) => name == x,
+            NodeMatcher::Suffix(x) => name.ends_with(x.as_str()),
+            NodeMatcher::Pattern(x) => x.is_match(name),
+        }
+    }
+}
+
+impl Iterator for Walk<'_> {
+    type Item = (u32, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let state = (self.node, self.instruction);
+
+        self.node = self.network.step(self.node, self.instruction);
+        self.instruction = (self.instruction + 1) % self.network.instructions.len();
+
+        Some(state)
+    }
+}
+
+impl GhostCycle {
+    pub fn is_hit(&self, step: u64) -> bool {
+        let step = match step < self.offset {
+            true => step,
+            false => self.offset + (step - self.offset) % self.period,
         };
 
-        steps += 1
+        self.hits.contains(&step)
     }
 
-    steps
+    fn cycle_hits(&self) -> impl Iterator<Item = u64> + '_ {
+        self.hits.iter().copied().filter(|x| *x >= self.offset)
+    }
 }
 
-pub fn calcualte_steps_with_multiple_starts(input: Vec<&str>) -> u64 {
-    let network = create_network(input.get(2..).unwrap().to_vec());
+fn analyse_ghost(network: &Network, start: u32, goals: &[bool]) -> GhostCycle {
+    let history = cycle_detection::hashed(network.walk(start)).unwrap();
+
+    GhostCycle {
+        start: network.name(start).to_string(),
+        offset: history.cycle.mu as u64,
+        period: history.cycle.lambda as u64,
+        hits: history
+            .states()
+            .iter()
+            .enumerate()
+            .filter(|(_, (node, _))| goals[*node as usize])
+            .map(|(i, _)| i as u64)
+            .collect(),
+    }
+}
+
+fn common_step(ghosts: &[GhostCycle]) -> Result<u64, String> {
+    if let Some(ghost) = ghosts.iter().find(|x| x.hits.is_empty()) {
+        return Err(format!("Ghost {} never reaches a goal node", ghost.start));
+    }
 
-    let starting_nodes = find_starting_nodes(network);
+    // A common step before every ghost entered its cycle has to be one of the
+    // pre-cycle hits of the ghost that enters its cycle last.
+    let Some(last) = ghosts.iter().max_by_key(|x| x.offset) else {
+        return Err("There are no ghosts to synchronise".to_string());
+    };
 
-    let steps = starting_nodes
+    let early = last
+        .hits
         .iter()
-        .map(|x| calculate_steps(input.clone(), x, "Z"))
-        .collect::<Vec<u64>>();
+        .copied()
+        .filter(|x| *x < last.offset)
+        .find(|x| ghosts.iter().all(|g| g.is_hit(*x)));
 
-    calculate_lcm(steps)
+    if let Some(step) = early {
+        return Ok(step);
+    }
+
+    let mut congruences = vec![(0, 1)];
+
+    for ghost in ghosts {
+        let mut next = vec![];
+
+        for congruence in &congruences {
+            for hit in ghost.cycle_hits() {
+                next.extend(crt(*congruence, (hit % ghost.period, ghost.period))?);
+            }
+        }
+
+        next.sort();
+        next.dedup();
+
+        if next.len() > MAX_CONGRUENCES {
+            return Err(format!(
+                "Too many candidate steps to combine: {} congruences after ghost {}",
+                next.len(),
+                ghost.start
+            ));
+        }
+
+        congruences = next;
+    }
+
+    let steps = congruences
+        .iter()
+        .map(|(r, m)| match *r >= last.offset {
+            true => Some(*r),
+            false => (last.offset - r)
+                .div_ceil(*m)
+                .checked_mul(*m)?
+                .checked_add(*r),
+        })
+        .collect::<Option<Vec<u64>>>()
+        .ok_or_else(|| "The common step does not fit in 64 bits".to_string())?;
+
+    steps.into_iter().min().ok_or_else(|| {
+        format!(
+            "No step puts all ghosts on a goal node at once: {}",
+            ghosts
+                .iter()
+                .map(|x| format!(
+                    "{} hits {:?} (offset {}, period {})",
+                    x.start, x.hits, x.offset, x.period
+                ))
+                .collect::<Vec<String>>()
+                .join(", ")
+        )
+    })
+}
+
+// Merges x = r1 (mod m1) and x = r2 (mod m2) into a single congruence, or
+// `Ok(None)` if they have no common solution. Both residues must already be
+// reduced by their modulus.
+fn crt((r1, m1): (u64, u64), (r2, m2): (u64, u64)) -> Result<Option<(u64, u64)>, String> {
+    let overflow = || {
+        format!(
+            "Combining {} mod {} with {} mod {} overflows 64 bits",
+            r1, m1, r2, m2
+        )
+    };
+
+    let (r1, m1, r2, m2) = (
+        i128::from(r1),
+        i128::from(m1),
+        i128::from(r2),
+        i128::from(m2),
+    );
+    let gcd = m1.extended_gcd(&m2);
+
+    if (r2 - r1) % gcd.gcd != 0 {
+        return Ok(None);
+    }
+
+    let m = (m1 / gcd.gcd)
+        .checked_mul(m2)
+        .and_then(|x| u64::try_from(x).ok())
+        .ok_or_else(overflow)?;
+
+    let step = m2 / gcd.gcd;
+    let t = ((r2 - r1) / gcd.gcd)
+        .rem_euclid(step)
+        .checked_mul(gcd.x.rem_euclid(step))
+        .ok_or_else(overflow)?
+        % step;
+    // r stays below m1 * step = m, which fits in 64 bits.
+    let r = r1 + t * m1;
+
+    Ok(Some((r as u64, m)))
 }
 
 fn calculate_lcm(steps: Vec<u64>) -> u64 {
@@ -74,20 +453,22 @@ fn parse_network_line(input: &str) -> (String, (String, String)) {
     )
 }
 
-fn parse_directions(input: &str) -> Cycle<Chars> {
-    input.chars().cycle()
-}
-
 #[test]
 fn shoud_parse_directions_as_cycle() {
-    let input = "LLR";
+    let network = Network::compile(vec!["LLR", "", "AAA = (AAA, AAA)"]);
+
+    let mut directions = network.walk(0).map(|(_, i)| network.instructions()[i]);
 
-    let mut directions = parse_directions(input);
+    assert_eq!(directions.next(), Some(0));
+    assert_eq!(directions.next(), Some(0));
+    assert_eq!(directions.next(), Some(1));
+    assert_eq!(directions.next(), Some(0));
+}
 
-    assert_eq!(directions.next(), Some('L'));
-    assert_eq!(directions.next(), Some('L'));
-    assert_eq!(directions.next(), Some('R'));
-    assert_eq!(directions.next(), Some('L'));
+#[test]
+#[should_panic(expected = "there should be at least one direction")]
+fn should_reject_empty_directions() {
+    Network::compile(vec!["", "", "AAA = (AAA, AAA)"]);
 }
 
 #[test]
@@ -117,10 +498,72 @@ fn should_create_network() {
 
 #[test]
 fn should_find_starting_nodes_that_end_with_a() {
-    let input = vec!["22A = (22B, XXX)", "11A = (11B, XXX)", "33C = (33B, XXX)"];
+    let input = vec![
+        "L",
+        "",
+        "22A = (22B, XXX)",
+        "11A = (11B, XXX)",
+        "33C = (33B, XXX)",
+        "11B = (XXX, XXX)",
+        "22B = (XXX, XXX)",
+        "33B = (XXX, XXX)",
+        "XXX = (XXX, XXX)",
+    ];
 
-    let network = create_network(input);
-    let starting_nodes = find_starting_nodes(network);
+    let network = Network::compile(input);
+    let starting_nodes = network.starts(&NodeMatcher::Suffix("A".to_string()));
 
-    assert_eq!(starting_nodes, vec!["11A", "22A"]);
+    assert_eq!(
+        starting_nodes
+            .iter()
+            .map(|x| network.name(*x))
+            .collect::<Vec<_>>(),
+        vec!["11A", "22A"]
+    );
+}
+
+#[test]
+fn should_combine_congruences() {
+    assert_eq!(crt((1, 2), (2, 3)), Ok(Some((5, 6))));
+    assert_eq!(crt((2, 4), (4, 6)), Ok(Some((10, 12))));
+    assert_eq!(crt((1, 4), (2, 6)), Ok(None));
+
+    let big: u64 = (1 << 31) + 11;
+    assert_eq!(
+        crt((big - 1, big), (big - 2, big - 1)),
+        Ok(Some((big * (big - 1) - 1, big * (big - 1))))
+    );
+    assert!(crt((1, u64::MAX), (0, u64::MAX - 1)).is_err());
+}
+
+#[test]
+fn should_compile_network_with_cycling_instructions() {
+    let input = vec![
+        "LLR",
+        "",
+        "AAA = (BBB, BBB)",
+        "BBB = (AAA, ZZZ)",
+        "ZZZ = (ZZZ, ZZZ)",
+    ];
+
+    let network = Network::compile(input);
+
+    assert_eq!(network.len(), 3);
+    assert_eq!(network.instructions(), &[0, 0, 1]);
+    assert_eq!(
+        network
+            .walk(network.id("AAA").unwrap())
+            .take(7)
+            .map(|(x, i)| (network.name(x), i))
+            .collect::<Vec<_>>(),
+        vec![
+            ("AAA", 0),
+            ("BBB", 1),
+            ("AAA", 2),
+            ("BBB", 0),
+            ("AAA", 1),
+            ("BBB", 2),
+            ("ZZZ", 0),
+        ]
+    );
 }
@@ -12,10 +12,12 @@ fn main() {
     println!("Trying to solve part 2...");
 
     let before = std::time::Instant::now();
-    let steps_with_multiple_starts =
-        day_8::calcualte_steps_with_multiple_starts(input.lines().collect());
+    let sync = day_8::synchronise_ghosts(input.lines().collect());
     let after = std::time::Instant::now();
 
     println!("Time taken: {:?}", after.duration_since(before));
-    println!("Solution is: {}", steps_with_multiple_starts);
+    match sync.steps {
+        Some(steps) => println!("Solution is: {}", steps),
+        None => println!("No solution: {}", sync.diagnostic.unwrap()),
+    }
 }
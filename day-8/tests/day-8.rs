@@ -32,3 +32,130 @@ fn should_calculate_steps_with_multiple_starting_nodes() {
 
     assert_eq!(steps, 6);
 }
+
+#[test]
+fn should_synchronise_ghosts_whose_first_hit_is_not_their_period() {
+    let input = vec![
+        "L",
+        "",
+        "11A = (11Z, 11Z)",
+        "11Z = (11B, 11B)",
+        "11B = (11Z, 11Z)",
+        "22A = (22B, 22B)",
+        "22B = (22Z, 22Z)",
+        "22Z = (22C, 22C)",
+        "22C = (22B, 22B)",
+    ];
+
+    let sync = day_8::synchronise_ghosts(input);
+
+    assert_eq!(
+        sync.ghosts,
+        vec![
+            day_8::GhostCycle {
+                start: "11A".to_string(),
+                offset: 1,
+                period: 2,
+                hits: vec![1],
+            },
+            day_8::GhostCycle {
+                start: "22A".to_string(),
+                offset: 1,
+                period: 3,
+                hits: vec![2],
+            },
+        ]
+    );
+    assert_eq!(sync.steps, Some(5));
+}
+
+#[test]
+fn should_report_when_ghosts_never_meet() {
+    let input = vec![
+        "L",
+        "",
+        "11A = (11Z, 11Z)",
+        "11Z = (11B, 11B)",
+        "11B = (11Z, 11Z)",
+        "22A = (22B, 22B)",
+        "22B = (22Z, 22Z)",
+        "22Z = (22B, 22B)",
+    ];
+
+    let sync = day_8::synchronise_ghosts(input);
+
+    assert_eq!(sync.steps, None);
+    assert!(sync
+        .diagnostic
+        .unwrap()
+        .starts_with("No step puts all ghosts"));
+}
+
+#[test]
+fn should_trace_route_between_exact_nodes() {
+    let input = vec![
+        "LLR",
+        "",
+        "AAA = (BBB, BBB)",
+        "BBB = (AAA, ZZZ)",
+        "ZZZ = (ZZZ, ZZZ)",
+    ];
+
+    let routes = day_8::find_routes(
+        input,
+        &day_8::NodeMatcher::Exact("AAA".to_string()),
+        &day_8::NodeMatcher::Exact("ZZZ".to_string()),
+        true,
+    );
+
+    assert_eq!(
+        routes,
+        vec![Ok(day_8::Route {
+            start: "AAA".to_string(),
+            steps: 6,
+            path: Some(
+                ["AAA", "BBB", "AAA", "BBB", "AAA", "BBB", "ZZZ"]
+                    .map(String::from)
+                    .to_vec()
+            ),
+        })]
+    );
+}
+
+#[test]
+fn should_select_starts_by_pattern_and_detect_unreachable_goal() {
+    let input = vec![
+        "LR",
+        "",
+        "11A = (11B, XXX)",
+        "11B = (XXX, 11Z)",
+        "11Z = (11B, XXX)",
+        "22A = (22B, XXX)",
+        "22B = (22C, 22C)",
+        "22C = (22Z, 22Z)",
+        "22Z = (22B, 22B)",
+        "XXX = (XXX, XXX)",
+    ];
+
+    let routes = day_8::find_routes(
+        input,
+        &day_8::NodeMatcher::Pattern(regex::Regex::new("^[12]{2}A$").unwrap()),
+        &day_8::NodeMatcher::Exact("22Z".to_string()),
+        false,
+    );
+
+    assert_eq!(
+        routes,
+        vec![
+            Err(day_8::RouteError::NeverReachesGoal {
+                start: "11A".to_string(),
+                explored: 16,
+            }),
+            Ok(day_8::Route {
+                start: "22A".to_string(),
+                steps: 3,
+                path: None,
+            }),
+        ]
+    );
+}
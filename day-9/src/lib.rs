@@ -1,10 +1,33 @@
+use std::ops::{Add, Mul};
+
 use lazy_static::lazy_static;
+use num_integer::Integer;
 use regex::Regex;
 
 lazy_static! {
     static ref NUMBER_REGEX: Regex = Regex::new(r"-?\d+").unwrap();
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Rational {
+    pub numerator: i128,
+    pub denominator: i128,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct History {
+    len: usize,
+    differences: Vec<i128>,
+}
+
+// Returns `None` once a value, a difference or the sum no longer fits in an
+// i128.
+pub fn extrapolate_sum(input: Vec<&str>, steps: i128) -> Option<i128> {
+    input.iter().try_fold(0_i128, |acc, x| {
+        acc.checked_add(History::parse(x)?.extrapolate(steps)?)
+    })
+}
+
 pub fn calc_extrapolated_values_to_right(input: Vec<&str>) -> i32 {
     input
         .iter()
@@ -88,6 +111,166 @@ fn parse_line(input: &str) -> Vec<i32> {
         .collect::<Vec<i32>>()
 }
 
+impl Rational {
+    // Reduces the fraction and keeps the sign on the numerator. Panics when the
+    // denominator is zero.
+    pub fn new(numerator: i128, denominator: i128) -> Self {
+        assert!(denominator != 0, "denominator should not be zero");
+
+        let gcd = numerator.gcd(&denominator) * denominator.signum();
+
+        Self {
+            numerator: numerator / gcd,
+            denominator: denominator / gcd,
+        }
+    }
+
+    pub fn checked_add(self, other: Self) -> Option<Self> {
+        let gcd = self.denominator.gcd(&other.denominator);
+        let denominator = (self.denominator / gcd).checked_mul(other.denominator)?;
+        let numerator = self
+            .numerator
+            .checked_mul(denominator / self.denominator)?
+            .checked_add(
+                other
+                    .numerator
+                    .checked_mul(denominator / other.denominator)?,
+            )?;
+
+        Some(Self::new(numerator, denominator))
+    }
+
+    // Cancels across the two fractions first, so that products of already
+    // reduced fractions only overflow when the result does.
+    pub fn checked_mul(self, other: Self) -> Option<Self> {
+        let left = self.numerator.gcd(&other.denominator);
+        let right = other.numerator.gcd(&self.denominator);
+
+        Some(Self::new(
+            (self.numerator / left).checked_mul(other.numerator / right)?,
+            (self.denominator / right).checked_mul(other.denominator / left)?,
+        ))
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Self::new(value, 1)
+    }
+}
+
+impl Add for Rational {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        self.checked_add(other)
+            .expect("rational addition overflows")
+    }
+}
+
+impl Mul for Rational {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        self.checked_mul(other)
+            .expect("rational multiplication overflows")
+    }
+}
+
+impl History {
+    // Returns `None` when a value or one of its differences does not fit in an
+    // i128.
+    pub fn parse(input: &str) -> Option<Self> {
+        NUMBER_REGEX
+            .find_iter(input)
+            .map(|x| x.as_str().parse::<i128>().ok())
+            .collect::<Option<Vec<i128>>>()
+            .and_then(Self::new)
+    }
+
+    // Returns `None` when one of the differences does not fit in an i128.
+    pub fn new(values: Vec<i128>) -> Option<Self> {
+        let len = values.len();
+        let mut differences = vec![];
+        let mut row = values;
+
+        while row.iter().any(|x| *x != 0) {
+            differences.push(row[0]);
+            row = row
+                .windows(2)
+                .map(|x| x[1].checked_sub(x[0]))
+                .collect::<Option<_>>()?;
+        }
+
+        Some(Self { len, differences })
+    }
+
+    pub fn degree(&self) -> usize {
+        self.differences.len().saturating_sub(1)
+    }
+
+    // Newton's forward-difference formula: f(x) = sum of C(x, j) * D^j f(0),
+    // where C(x, j) stays an integer for negative x as well. Returns `None`
+    // when a term or the value does not fit in an i128.
+    pub fn value_at(&self, x: i128) -> Option<i128> {
+        let mut binomial = 1_i128;
+        let mut value = 0_i128;
+
+        for (j, difference) in self.differences.iter().enumerate() {
+            if j > 0 {
+                let j = j as i128;
+                binomial = binomial.checked_mul(x.checked_sub(j - 1)?)? / j;
+            }
+
+            value = value.checked_add(binomial.checked_mul(*difference)?)?;
+        }
+
+        Some(value)
+    }
+
+    pub fn extrapolate(&self, steps: i128) -> Option<i128> {
+        match steps >= 0 {
+            true => self.value_at((self.len as i128 - 1).checked_add(steps)?),
+            false => self.value_at(steps),
+        }
+    }
+
+    // Returns `None` when a coefficient does not fit in an i128 fraction, which
+    // happens from degree 34 on since 34! overflows.
+    pub fn coefficients(&self) -> Option<Vec<Rational>> {
+        let mut coefficients = vec![Rational::from(0); self.differences.len().max(1)];
+        let mut falling = vec![1_i128];
+        let mut factorial = 1_i128;
+
+        for (j, difference) in self.differences.iter().enumerate() {
+            if j > 0 {
+                falling = multiply_by_root(&falling, j as i128 - 1)?;
+                factorial = factorial.checked_mul(j as i128)?;
+            }
+
+            let scale = Rational::new(*difference, factorial);
+
+            for (power, coefficient) in falling.iter().enumerate() {
+                coefficients[power] = coefficients[power]
+                    .checked_add(scale.checked_mul(Rational::from(*coefficient))?)?;
+            }
+        }
+
+        Some(coefficients)
+    }
+}
+
+fn multiply_by_root(polynomial: &[i128], root: i128) -> Option<Vec<i128>> {
+    let mut result = vec![0_i128; polynomial.len() + 1];
+
+    for (power, coefficient) in polynomial.iter().enumerate() {
+        result[power + 1] = result[power + 1].checked_add(*coefficient)?;
+        result[power] = result[power].checked_sub(coefficient.checked_mul(root)?)?;
+    }
+
+    Some(result)
+}
+
 #[test]
 fn should_parse_a_line() {
     let input = "0 3 6 9 12 15";
@@ -150,3 +333,73 @@ fn should_return_minus_three_for_extrapoloation_to_left() {
 
     assert_eq!(differences, 3);
 }
+
+#[test]
+fn should_extrapolate_any_number_of_steps() {
+    let history = History::new(vec![1, 3, 6, 10, 15, 21]).unwrap();
+
+    assert_eq!(history.extrapolate(1), Some(28));
+    assert_eq!(history.extrapolate(3), Some(45));
+    assert_eq!(history.extrapolate(-1), Some(0));
+    assert_eq!(history.extrapolate(-3), Some(1));
+    assert_eq!(history.extrapolate(1_000_000), Some(500_006_500_021));
+}
+
+#[test]
+fn should_expose_fitted_polynomial() {
+    let history = History::new(vec![1, 3, 6, 10, 15, 21]).unwrap();
+
+    assert_eq!(history.degree(), 2);
+    assert_eq!(
+        history.coefficients(),
+        Some(vec![
+            Rational::new(1, 1),
+            Rational::new(3, 2),
+            Rational::new(1, 2)
+        ])
+    );
+}
+
+#[test]
+fn should_report_overflow_near_i128_limits() {
+    let squares = History::new(vec![0, 1, 4]).unwrap();
+
+    assert_eq!(squares.value_at(1 << 63), Some(1 << 126));
+    assert_eq!(squares.value_at(1 << 64), None);
+    assert_eq!(squares.extrapolate(i128::MAX), None);
+
+    // C(x, n) is 0 for x < n and 1 for x = n, so its only non-zero difference
+    // is the n-th one and its leading coefficient is 1 / n!.
+    let binomial = |n: usize| History::new([vec![0; n], vec![1]].concat()).unwrap();
+
+    let coefficients = binomial(33).coefficients().unwrap();
+    assert_eq!(binomial(33).degree(), 33);
+    assert_eq!(
+        coefficients[33],
+        Rational::new(1, (1..=33).product::<i128>())
+    );
+    assert_eq!(binomial(34).coefficients(), None);
+}
+
+#[test]
+fn should_reject_histories_beyond_i128() {
+    let extremes = format!("{} {}", i128::MIN, i128::MAX);
+
+    assert_eq!(History::parse(&extremes), None);
+    assert_eq!(History::new(vec![i128::MIN, i128::MAX]), None);
+    assert_eq!(
+        History::parse("170141183460469231731687303715884105728"),
+        None
+    );
+    assert_eq!(extrapolate_sum(vec!["1 2 3", &extremes], 1), None);
+    assert_eq!(
+        History::parse(&format!("{} -1", i128::MIN)).map(|x| x.degree()),
+        Some(1)
+    );
+}
+
+#[test]
+#[should_panic(expected = "denominator should not be zero")]
+fn should_reject_zero_denominator() {
+    Rational::new(1, 0);
+}
@@ -4,8 +4,8 @@ fn main() {
         std::fs::read_to_string(path).expect("Should have been able to read the file input.txt");
 
     println!("Trying to solve part 1...");
-    let extrapolated_values_right =
-        day_9::calc_extrapolated_values_to_right(input.lines().collect());
+    let extrapolated_values_right = day_9::extrapolate_sum(input.lines().collect(), 1)
+        .expect("extrapolated values should fit in an i128");
 
     println!("Solution is: {}", extrapolated_values_right);
 
@@ -13,7 +13,8 @@ fn main() {
         std::fs::read_to_string(path).expect("Should have been able to read the file input.txt");
 
     println!("Trying to solve part 2...");
-    let extrapolated_values_left = day_9::calc_extrapolated_values_to_left(input.lines().collect());
+    let extrapolated_values_left = day_9::extrapolate_sum(input.lines().collect(), -1)
+        .expect("extrapolated values should fit in an i128");
 
     println!("Solution is: {}", extrapolated_values_left);
 }
@@ -15,3 +15,11 @@ fn should_calc_extrapolated_values_left() {
 
     assert_eq!(result, 2);
 }
+
+#[test]
+fn should_extrapolate_sum_in_both_directions() {
+    let input = vec!["0 3 6 9 12 15", "1 3 6 10 15 21", "10 13 16 21 30 45"];
+
+    assert_eq!(day_9::extrapolate_sum(input.clone(), 1), Some(114));
+    assert_eq!(day_9::extrapolate_sum(input, -1), Some(2));
+}
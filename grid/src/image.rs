@@ -0,0 +1,138 @@
+use std::{collections::HashSet, io, path::Path};
+
+use crate::{Grid, Pos};
+
+pub type Rgb = [u8; 3];
+
+// Every grid cell becomes a `scale` x `scale` block of pixels so that small
+// puzzle inputs stay readable.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Image {
+    pixels: Grid<Rgb>,
+    scale: usize,
+}
+
+impl Image {
+    pub fn render<T>(grid: &Grid<T>, palette: impl Fn(&T) -> Rgb) -> Self {
+        Self {
+            pixels: grid.map(palette),
+            scale: 1,
+        }
+    }
+
+    // Overlaid cells are mixed half and half with the map below, so the map
+    // stays visible underneath the solver's result. Repeated cells are mixed
+    // only once.
+    pub fn overlay(mut self, cells: impl IntoIterator<Item = Pos>, color: Rgb) -> Self {
+        for pos in cells.into_iter().collect::<HashSet<Pos>>() {
+            if let Some(pixel) = self.pixels.get_mut(pos) {
+                for (channel, over) in pixel.iter_mut().zip(color) {
+                    *channel = ((*channel as u16 + over as u16) / 2) as u8;
+                }
+            }
+        }
+
+        self
+    }
+
+    pub fn scaled(mut self, scale: usize) -> Self {
+        self.scale = scale.max(1);
+        self
+    }
+
+    pub fn width(&self) -> usize {
+        self.pixels.cols() * self.scale
+    }
+
+    pub fn height(&self) -> usize {
+        self.pixels.rows() * self.scale
+    }
+
+    // Pixels are addressed like grid cells, as (row, col) of the scaled image.
+    pub fn pixel(&self, (r, c): Pos) -> Option<Rgb> {
+        self.pixels.get((r / self.scale, c / self.scale)).copied()
+    }
+
+    pub fn to_ppm(&self) -> Vec<u8> {
+        let header = format!("P6\n{} {}\n255\n", self.width(), self.height());
+
+        header.into_bytes().into_iter().chain(self.rgb()).collect()
+    }
+
+    // PNG cannot hold an empty image, nor one wider or taller than `u32::MAX`.
+    pub fn to_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let width = u32::try_from(self.width()).map_err(|_| png::EncodingError::LimitsExceeded)?;
+        let height =
+            u32::try_from(self.height()).map_err(|_| png::EncodingError::LimitsExceeded)?;
+        let mut bytes = vec![];
+
+        let mut encoder = png::Encoder::new(&mut bytes, width, height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        encoder
+            .write_header()
+            .and_then(|mut writer| writer.write_image_data(&self.rgb()))?;
+
+        Ok(bytes)
+    }
+
+    // The format follows the extension, anything but `.png` is written as PPM.
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+
+        let bytes = match path.extension().and_then(|x| x.to_str()) {
+            Some("png") => self.to_png()?,
+            _ => self.to_ppm(),
+        };
+
+        std::fs::write(path, bytes)
+    }
+
+    fn rgb(&self) -> Vec<u8> {
+        (0..self.height())
+            .flat_map(|r| (0..self.width()).map(move |c| (r, c)))
+            .flat_map(|pos| self.pixel(pos).unwrap())
+            .collect()
+    }
+}
+
+#[test]
+fn should_render_with_palette_and_overlay() {
+    let grid = Grid::parse(&["#.", ".#"]);
+
+    let image = Image::render(&grid, |x| match x {
+        b'#' => [0, 0, 0],
+        _ => [200, 200, 200],
+    })
+    .overlay([(0, 1), (5, 5), (0, 1)], [255, 0, 0])
+    .scaled(2);
+
+    assert_eq!((image.width(), image.height()), (4, 4));
+    assert_eq!(image.pixel((0, 0)), Some([0, 0, 0]));
+    assert_eq!(image.pixel((1, 3)), Some([227, 100, 100]));
+    assert_eq!(image.pixel((3, 3)), Some([0, 0, 0]));
+}
+
+#[test]
+fn should_encode_ppm_and_png() {
+    let image = Image::render(&Grid::parse(&["ab"]), |x| [*x, 0, 1]);
+
+    assert_eq!(image.to_ppm(), b"P6\n2 1\n255\n\x61\x00\x01\x62\x00\x01");
+
+    let png = image.to_png().unwrap();
+    let decoder = png::Decoder::new(std::io::Cursor::new(png));
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size().unwrap()];
+    reader.next_frame(&mut pixels).unwrap();
+
+    assert_eq!(pixels, [0x61, 0, 1, 0x62, 0, 1]);
+}
+
+#[test]
+fn should_reject_empty_png() {
+    let image = Image::render(&Grid::parse(&[]), |x| [*x, 0, 0]);
+
+    assert!(image.to_png().is_err());
+    assert!(image.save(std::env::temp_dir().join("empty.png")).is_err());
+}
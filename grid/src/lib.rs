@@ -0,0 +1,295 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+};
+
+pub mod image;
+
+pub type Pos = (usize, usize);
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
+pub enum Dir {
+    North,
+    East,
+    South,
+    West,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    rows: usize,
+    cols: usize,
+}
+
+impl Dir {
+    pub const ALL: [Dir; 4] = [Dir::North, Dir::East, Dir::South, Dir::West];
+
+    pub fn turn_left(self) -> Self {
+        match self {
+            Dir::North => Dir::West,
+            Dir::East => Dir::North,
+            Dir::South => Dir::East,
+            Dir::West => Dir::South,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        self.turn_left().opposite()
+    }
+
+    pub fn opposite(self) -> Self {
+        match self {
+            Dir::North => Dir::South,
+            Dir::East => Dir::West,
+            Dir::South => Dir::North,
+            Dir::West => Dir::East,
+        }
+    }
+
+    pub fn delta(self) -> (isize, isize) {
+        match self {
+            Dir::North => (-1, 0),
+            Dir::East => (0, 1),
+            Dir::South => (1, 0),
+            Dir::West => (0, -1),
+        }
+    }
+}
+
+impl Grid<u8> {
+    // Every line becomes one row of raw bytes, so lines must share a length.
+    pub fn parse(input: &[&str]) -> Self {
+        let cols = input.first().map_or(0, |x| x.len());
+
+        assert!(
+            input.iter().all(|x| x.len() == cols),
+            "every line should have {} bytes",
+            cols
+        );
+
+        Self {
+            cells: input.iter().flat_map(|x| x.bytes()).collect(),
+            rows: input.len(),
+            cols,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn from_vec(rows: usize, cols: usize, cells: Vec<T>) -> Self {
+        assert_eq!(rows * cols, cells.len(), "cells should fill the grid");
+
+        Self { cells, rows, cols }
+    }
+
+    pub fn filled(rows: usize, cols: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_vec(rows, cols, vec![value; rows * cols])
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn contains(&self, (r, c): Pos) -> bool {
+        r < self.rows && c < self.cols
+    }
+
+    pub fn get(&self, pos: Pos) -> Option<&T> {
+        self.contains(pos).then(|| &self[pos])
+    }
+
+    pub fn get_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        match self.contains(pos) {
+            true => Some(&mut self[pos]),
+            false => None,
+        }
+    }
+
+    pub fn offset(&self, (r, c): Pos, (dr, dc): (isize, isize)) -> Option<Pos> {
+        let pos = (r.checked_add_signed(dr)?, c.checked_add_signed(dc)?);
+
+        self.contains(pos).then_some(pos)
+    }
+
+    pub fn step(&self, pos: Pos, dir: Dir) -> Option<Pos> {
+        self.offset(pos, dir.delta())
+    }
+
+    pub fn neighbours(&self, pos: Pos) -> impl Iterator<Item = (Pos, Dir)> + '_ {
+        Dir::ALL
+            .into_iter()
+            .filter_map(move |dir| self.step(pos, dir).map(|x| (x, dir)))
+    }
+
+    pub fn neighbours8(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        (-1..=1)
+            .flat_map(|dr| (-1..=1).map(move |dc| (dr, dc)))
+            .filter(|delta| *delta != (0, 0))
+            .filter_map(move |delta| self.offset(pos, delta))
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Pos> {
+        let cols = self.cols;
+
+        (0..self.rows).flat_map(move |r| (0..cols).map(move |c| (r, c)))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    pub fn position(&self, predicate: impl Fn(&T) -> bool) -> Option<Pos> {
+        self.iter().find(|(_, x)| predicate(x)).map(|(pos, _)| pos)
+    }
+
+    pub fn row(&self, r: usize) -> &[T] {
+        &self.cells[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn row_mut(&mut self, r: usize) -> &mut [T] {
+        &mut self.cells[r * self.cols..(r + 1) * self.cols]
+    }
+
+    pub fn col(&self, c: usize) -> impl Iterator<Item = &T> {
+        self.cells.iter().skip(c).step_by(self.cols.max(1))
+    }
+
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn map<U>(&self, f: impl Fn(&T) -> U) -> Grid<U> {
+        Grid::from_vec(self.rows, self.cols, self.cells.iter().map(f).collect())
+    }
+
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.cols, self.rows, |(r, c)| (c, r))
+    }
+
+    pub fn rotate_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.cols, self.rows, |(r, c)| (self.rows - 1 - c, r))
+    }
+
+    pub fn rotate_counter_clockwise(&self) -> Self
+    where
+        T: Clone,
+    {
+        self.remap(self.cols, self.rows, |(r, c)| (c, self.cols - 1 - r))
+    }
+
+    // Builds a rows x cols grid whose cell at `pos` is the one at `source(pos)`
+    // in this grid.
+    fn remap(&self, rows: usize, cols: usize, source: impl Fn(Pos) -> Pos) -> Self
+    where
+        T: Clone,
+    {
+        let cells = (0..rows)
+            .flat_map(|r| (0..cols).map(move |c| (r, c)))
+            .map(|pos| self[source(pos)].clone())
+            .collect();
+
+        Self::from_vec(rows, cols, cells)
+    }
+}
+
+impl<T> Index<Pos> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (r, c): Pos) -> &T {
+        assert!(c < self.cols, "column {} out of bounds", c);
+
+        &self.cells[r * self.cols + c]
+    }
+}
+
+impl<T> IndexMut<Pos> for Grid<T> {
+    fn index_mut(&mut self, (r, c): Pos) -> &mut T {
+        assert!(c < self.cols, "column {} out of bounds", c);
+
+        &mut self.cells[r * self.cols + c]
+    }
+}
+
+impl<T: Copy + Into<char>> fmt::Display for Grid<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for r in 0..self.rows {
+            if r > 0 {
+                writeln!(f)?;
+            }
+
+            for cell in self.row(r) {
+                write!(f, "{}", (*cell).into())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn should_parse_and_display() {
+    let grid = Grid::parse(&["#..", ".#."]);
+
+    assert_eq!((grid.rows(), grid.cols()), (2, 3));
+    assert_eq!(grid[(1, 1)], b'#');
+    assert_eq!(grid.get((2, 0)), None);
+    assert_eq!(grid.get((0, 3)), None);
+    assert_eq!(grid.to_string(), "#..\n.#.");
+}
+
+#[test]
+fn should_find_checked_neighbours() {
+    let grid = Grid::filled(3, 3, 0);
+
+    assert_eq!(
+        grid.neighbours((0, 0)).collect::<Vec<_>>(),
+        vec![((0, 1), Dir::East), ((1, 0), Dir::South)]
+    );
+    assert_eq!(grid.neighbours((1, 1)).count(), 4);
+    assert_eq!(
+        grid.neighbours8((0, 2)).collect::<Vec<_>>(),
+        vec![(0, 1), (1, 1), (1, 2)]
+    );
+    assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+}
+
+#[test]
+fn should_turn_directions() {
+    for dir in Dir::ALL {
+        assert_eq!(dir.turn_left().turn_right(), dir);
+        assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+
+        let (dr, dc) = dir.delta();
+        assert_eq!(dir.opposite().delta(), (-dr, -dc));
+    }
+
+    assert_eq!(Dir::North.turn_right(), Dir::East);
+}
+
+#[test]
+fn should_rotate_and_transpose() {
+    let grid = Grid::parse(&["ab", "cd", "ef"]);
+
+    assert_eq!(grid.transpose().to_string(), "ace\nbdf");
+    assert_eq!(grid.rotate_clockwise().to_string(), "eca\nfdb");
+    assert_eq!(grid.rotate_counter_clockwise().to_string(), "bdf\nace");
+    assert_eq!(
+        grid.rotate_clockwise().rotate_counter_clockwise(),
+        grid.clone()
+    );
+    assert_eq!(grid.col(1).copied().collect::<Vec<_>>(), b"bdf");
+}